@@ -34,6 +34,10 @@ mod maar {
         /// Mapping of the token amount which an account is allowed to withdraw
         /// from another account.
         allowances: StorageHashMap<(AccountId, AccountId), Balance>,
+        /// Account allowed to mint, the deployer.
+        owner: AccountId,
+        /// Upper bound for `total_supply`, `None` if minting is uncapped.
+        max_supply: Option<Balance>,
     }
 
     /// Event emitted when a token transfer occurs.
@@ -67,6 +71,12 @@ mod maar {
         InsufficientBalance,
         /// Returned if not enough allowance to fulfill a request is available.
         InsufficientAllowance,
+        /// Returned if the caller is not the contract owner.
+        NotOwner,
+        /// Returned if minting would push the total supply past the cap.
+        SupplyCapExceeded,
+        /// Returned if raising an allowance would overflow.
+        AllowanceOverflow,
     }
 
     /// The ERC-20 result type.
@@ -76,6 +86,20 @@ mod maar {
         /// Creates a new ERC-20 contract with the specified initial supply.
         #[ink(constructor)]
         pub fn new(initial_supply: Balance) -> Self {
+            Self::new_with_cap(initial_supply, None)
+        }
+
+        /// Creates a new ERC-20 contract whose supply can be minted up to `max_supply`.
+        ///
+        /// # Panics
+        ///
+        /// If `initial_supply` is already above `max_supply`.
+        #[ink(constructor)]
+        pub fn new_with_cap(initial_supply: Balance, max_supply: Option<Balance>) -> Self {
+            assert!(
+                max_supply.map_or(true, |cap| initial_supply <= cap),
+                "initial supply exceeds the cap"
+            );
             let caller = Self::env().caller();
             let mut balances = StorageHashMap::new();
             balances.insert(caller, initial_supply);
@@ -83,6 +107,8 @@ mod maar {
                 total_supply: Lazy::new(initial_supply),
                 balances,
                 allowances: StorageHashMap::new(),
+                owner: caller,
+                max_supply,
             };
             Self::env().emit_event(Transfer {
                 from: None,
@@ -98,6 +124,70 @@ mod maar {
             *self.total_supply
         }
 
+        /// Returns the supply cap, `None` if minting is uncapped.
+        #[ink(message)]
+        pub fn max_supply(&self) -> Option<Balance> {
+            self.max_supply
+        }
+
+        /// Creates `value` new tokens on the account `to`.
+        ///
+        /// On success a `Transfer` event with no `from` is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotOwner` error if the caller is not the deployer.
+        ///
+        /// Returns `SupplyCapExceeded` error if the new total supply would be
+        /// above the cap or overflow.
+        #[ink(message)]
+        pub fn mint(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            let total_supply = self
+                .total_supply
+                .checked_add(value)
+                .ok_or(Error::SupplyCapExceeded)?;
+            if self.max_supply.map_or(false, |cap| total_supply > cap) {
+                return Err(Error::SupplyCapExceeded)
+            }
+            *self.total_supply = total_supply;
+            let to_balance = self.balance_of(to);
+            self.balances.insert(to, to_balance + value);
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(to),
+                value,
+            });
+            Ok(())
+        }
+
+        /// Destroys `value` tokens from the caller's account.
+        ///
+        /// On success a `Transfer` event with no `to` is emitted.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the caller's account balance.
+        #[ink(message)]
+        pub fn burn(&mut self, value: Balance) -> Result<()> {
+            let from = self.env().caller();
+            let from_balance = self.balance_of(from);
+            if from_balance < value {
+                return Err(Error::InsufficientBalance)
+            }
+            self.balances.insert(from, from_balance - value);
+            *self.total_supply -= value;
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: None,
+                value,
+            });
+            Ok(())
+        }
+
         /// Returns the account balance for the specified `owner`.
         ///
         /// Returns `0` if the account is non-existent.
@@ -146,6 +236,35 @@ mod maar {
             Ok(())
         }
 
+        /// Raises the allowance of `spender` on the caller's account by `delta`.
+        ///
+        /// Unlike `approve` this builds on the current allowance, so a pending
+        /// `transfer_from` can't spend both the old and the new value.
+        ///
+        /// An `Approval` event with the resulting allowance is emitted.
+        ///
+        /// Returns `AllowanceOverflow` if the allowance would exceed `Balance::MAX`.
+        #[ink(message)]
+        pub fn increase_allowance(&mut self, spender: AccountId, delta: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let value = self
+                .allowance(owner, spender)
+                .checked_add(delta)
+                .ok_or(Error::AllowanceOverflow)?;
+            self.approve(spender, value)
+        }
+
+        /// Lowers the allowance of `spender` on the caller's account by `delta`,
+        /// down to `0` at most.
+        ///
+        /// An `Approval` event with the resulting allowance is emitted.
+        #[ink(message)]
+        pub fn decrease_allowance(&mut self, spender: AccountId, delta: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let value = self.allowance(owner, spender).saturating_sub(delta);
+            self.approve(spender, value)
+        }
+
         /// Transfers `value` tokens on the behalf of `from` to the account `to`.
         ///
         /// This can be used to allow a contract to transfer tokens on ones behalf and/or
@@ -473,6 +592,169 @@ mod maar {
             );
         }
 
+        #[ink::test]
+        fn mint_works() {
+            let mut maar = Maar::new_with_cap(100, Some(150));
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            assert_eq!(maar.max_supply(), Some(150));
+
+            // Alice mints below the cap, then exactly up to it.
+            assert_eq!(maar.mint(accounts.bob, 40), Ok(()));
+            assert_eq!(maar.mint(accounts.bob, 10), Ok(()));
+            assert_eq!(maar.total_supply(), 150);
+            assert_eq!(maar.balance_of(accounts.bob), 50);
+
+            // One more token is above the cap.
+            assert_eq!(maar.mint(accounts.bob, 1), Err(Error::SupplyCapExceeded));
+            assert_eq!(maar.total_supply(), 150);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 3);
+            assert_transfer_event(
+                &emitted_events[1],
+                None,
+                Some(AccountId::from([0x02; 32])),
+                40,
+            );
+        }
+
+        #[ink::test]
+        fn uncapped_mint_overflow_should_fail() {
+            let mut maar = Maar::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            assert_eq!(maar.max_supply(), None);
+            assert_eq!(
+                maar.mint(accounts.bob, Balance::MAX),
+                Err(Error::SupplyCapExceeded)
+            );
+        }
+
+        #[ink::test]
+        fn unauthorized_mint_should_fail() {
+            let mut maar = Maar::new_with_cap(100, Some(150));
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            // Get contract address.
+            let callee = ink_env::account_id::<ink_env::DefaultEnvironment>()
+                .unwrap_or([0x0; 32].into());
+            // Create call.
+            let mut data =
+                ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4])); // balance_of
+            data.push_arg(&accounts.bob);
+            // Push the new execution context to set Bob as caller.
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                accounts.bob,
+                callee,
+                1000000,
+                1000000,
+                data,
+            );
+
+            assert_eq!(maar.mint(accounts.bob, 10), Err(Error::NotOwner));
+            assert_eq!(maar.total_supply(), 100);
+            assert_eq!(maar.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        #[should_panic]
+        fn initial_supply_above_cap_should_fail() {
+            Maar::new_with_cap(100, Some(99));
+        }
+
+        #[ink::test]
+        fn burn_works() {
+            let mut maar = Maar::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            // Alice burns 30 of her tokens.
+            assert_eq!(maar.burn(30), Ok(()));
+            assert_eq!(maar.balance_of(accounts.alice), 70);
+            assert_eq!(maar.total_supply(), 70);
+
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 2);
+            assert_transfer_event(
+                &emitted_events[1],
+                Some(AccountId::from([0x01; 32])),
+                None,
+                30,
+            );
+        }
+
+        #[ink::test]
+        fn over_burn_should_fail() {
+            let mut maar = Maar::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(maar.burn(101), Err(Error::InsufficientBalance));
+            assert_eq!(maar.balance_of(accounts.alice), 100);
+            assert_eq!(maar.total_supply(), 100);
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+        }
+
+        #[ink::test]
+        fn increase_and_decrease_allowance_work() {
+            let mut maar = Maar::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(maar.increase_allowance(accounts.bob, 10), Ok(()));
+            assert_eq!(maar.increase_allowance(accounts.bob, 5), Ok(()));
+            assert_eq!(maar.allowance(accounts.alice, accounts.bob), 15);
+            assert_eq!(maar.decrease_allowance(accounts.bob, 4), Ok(()));
+            assert_eq!(maar.allowance(accounts.alice, accounts.bob), 11);
+            // Decreasing below zero leaves no allowance.
+            assert_eq!(maar.decrease_allowance(accounts.bob, 20), Ok(()));
+            assert_eq!(maar.allowance(accounts.alice, accounts.bob), 0);
+
+            // Every change emits an Approval with the resulting value.
+            let values = ink_env::test::recorded_events()
+                .skip(1)
+                .map(|event| {
+                    let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer");
+                    if let Event::Approval(Approval { owner, spender, value }) = decoded_event {
+                        assert_eq!(owner, accounts.alice, "encountered invalid Approval.owner");
+                        assert_eq!(spender, accounts.bob, "encountered invalid Approval.spender");
+                        value
+                    } else {
+                        panic!("encountered unexpected event kind: expected an Approval event")
+                    }
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(values, vec![10, 15, 11, 0]);
+        }
+
+        #[ink::test]
+        fn increase_allowance_overflow_should_fail() {
+            let mut maar = Maar::new(100);
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            assert_eq!(maar.approve(accounts.bob, Balance::MAX - 1), Ok(()));
+            assert_eq!(
+                maar.increase_allowance(accounts.bob, 2),
+                Err(Error::AllowanceOverflow)
+            );
+            // The allowance is left as it was and no event is emitted.
+            assert_eq!(maar.allowance(accounts.alice, accounts.bob), Balance::MAX - 1);
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+            assert_eq!(maar.increase_allowance(accounts.bob, 1), Ok(()));
+            assert_eq!(maar.allowance(accounts.alice, accounts.bob), Balance::MAX);
+        }
+
         #[ink::test]
         fn allowance_must_not_change_on_failed_transfer() {
             let mut maar = Maar::new(100);
@@ -7,29 +7,48 @@ mod raffle {
     #[cfg(not(feature = "ink-as-dependency"))]
     use ink_storage::{
         collections::{
+            HashMap as StorageHashMap,
             Vec as InkVec,
-        }
+        },
+        traits::push_spread_root,
     };
+    use ink_primitives::Key;
+    use ink_prelude::vec::Vec;
 
     //A user can send in anywhere between 0.01 and 0.1 tokens.
     const DEPOSIT_MIN: u128 =  10_000_000_000_000;
     const DEPOSIT_MAX: u128 = 100_000_000_000_000;
 
     // countdown only starts once there are at least RAFFLE_TRIGGER players in the pool
+    // default, new_with_config can pick another trigger
     const RAFFLE_TRIGGER: u32 = 5; 
 
-    /// Number of rafflr winners
-    const RAFFLE_WINNERS: u8 = 2;
+    /// Default number of raffle winners
+    const RAFFLE_WINNERS: u32 = 2;
+
+    // draw_winner needs a non-winner left in the pool for every draw,
+    // new_with_config checks the same at runtime
+    const _: () = assert!(RAFFLE_WINNERS <= RAFFLE_TRIGGER);
 
-    /// Duration before draw is enabled 15min x 60sec x 1000ms
+    /// Default duration before draw is enabled, a real deployment passes
+    /// e.g. 15min x 60sec x 1000ms to new_with_config
     const DURATION_IN_MS: u64 = 5;
 
+    /// Largest batch a single call may process without risking the gas limit
+    const MAX_BATCH_SIZE: u32 = 16;
+
+    /// House fee cap, 1000 basis points = 10%
+    const MAX_FEE_BPS: u16 = 1000;
+
+    /// Default time winners have to withdraw() before sweep_unclaimed(), 30 days
+    const CLAIM_GRACE_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+
 
     /// The Raffle error types.
     #[derive(Debug, PartialEq, Eq, scale::Encode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
-        /// Returned if not DEPOSIT_MIN < payment < DEPOSIT_MAX
+        /// Returned if not deposit_min < payment < deposit_max
         EndowmentOutOfLimits,
 
         /// Returned if account already in the game
@@ -46,64 +65,571 @@ mod raffle {
 
         /// Raffle time countdown not finished
         RaffleStillOpen,
+
+        /// Participant account holds less than min_account_balance
+        AccountBalanceTooLow,
+
+        /// Deposit would push the pot over max_pot
+        PotCapReached,
+
+        /// Caller has nothing to withdraw
+        NothingToWithdraw,
+
+        /// Caller is not the contract owner
+        NotOwner,
+
+        /// Payouts are frozen by the owner
+        WithdrawalsPaused,
+
+        /// Raffle does not accept entries before scheduled_open_ms
+        NotYetOpen,
+
+        /// Too many entries in the current block
+        RateLimited,
+
+        /// Draw must wait until the chain is past target_block
+        TargetBlockNotReached,
+
+        /// Target block must be in the future and set before the first draw
+        InvalidTargetBlock,
+
+        /// Pot would overflow Balance
+        BalanceOverflow,
+
+        /// Constructor parameters are inconsistent
+        InvalidConfig,
+
+        /// Raffle can only be reset once all winners are drawn
+        RaffleInProgress,
+
+        /// Entries are frozen by the owner
+        Paused,
+
+        /// Raffle was cancelled and deposits refunded
+        RaffleCancelled,
+
+        /// Caller has not entered the raffle
+        NotParticipating,
+
+        /// Entries are locked once the countdown started
+        CountdownStarted,
+
+        /// Raffle is staked in its ERC20 token, use participate_with_token()
+        TokenStakeOnly,
+
+        /// Raffle is staked in the native token, use participate()
+        NativeStakeOnly,
+
+        /// pot_receiver may not enter this raffle
+        ReceiverCannotParticipate,
+
+        /// Batch has more entries than MAX_BATCH_SIZE or mismatched lists
+        InvalidBatch,
+
+        /// Participant is not on the allowlist
+        NotAllowed,
+
+        /// Pool already holds max_participants
+        PoolFull,
+
+        /// participate() was called without a payment
+        NoPaymentReceived,
+
+        /// Owner closed the entry period with close_entries()
+        EntriesClosed,
+
+        /// Winners still have time to claim before sweep_unclaimed()
+        GracePeriodActive,
+
+        /// Account already holds max_entries_per_account tickets
+        EntryLimitReached,
+
+        /// draw_winner() was entered again while it was running
+        Reentrancy,
+
+        /// Caller is not the account named in transfer_ownership()
+        NotPendingOwner,
+
+        /// entry_deadline passed before the pool reached the trigger
+        EntryPeriodEnded,
+
+        /// Another account paid the participant's earlier tickets
+        PayerMismatch,
     }
 
     /// The Raffle result type.
     pub type Result<T> = core::result::Result<T, Error>;
 
+    /// Where the raffle stands, see status()
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum RaffleStatus {
+        /// Fewer than trigger participants, countdown not started
+        AwaitingParticipants,
+
+        /// Countdown running, draws are rejected
+        CountdownActive,
+
+        /// Countdown elapsed, winners can be drawn
+        ReadyToDraw,
+
+        /// All winners drawn, or the raffle was cancelled
+        Completed,
+    }
+
+    /// Raffle state in one call for dashboards, see snapshot()
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct RaffleSnapshot {
+        pub participant_count: u32,
+        pub total_balance: Balance,
+        pub winners_drawn: u32,
+        /// 0 until the countdown is triggered
+        pub start_time: u64,
+        /// Same as remaining_time()
+        pub remaining_time: u64,
+        pub status: RaffleStatus,
+    }
+
     /// This is the storage of Raffle contract.
     #[ink(storage)]
     #[derive()]
     pub struct Raffle {
+        owner: AccountId,
         pot_receiver: AccountId,
         total_balance: Balance,
+        deposit_min: Balance,
+        deposit_max: Balance,
         enough_participants: bool,
-        winners: u8,
+        winners: u32,
+        num_winners: u32,
         participant_list: InkVec<AccountId>,
-        winner_list: [Option<AccountId>; RAFFLE_WINNERS as usize],
+        /// The draw pool, one entry per ticket so an account can appear
+        /// more than once. participant_list holds every account once.
+        tickets: InkVec<AccountId>,
+        deposits: StorageHashMap<AccountId, Balance>,
+        joined_at: StorageHashMap<AccountId, u64>,
+        /// Who paid each participant's deposit, refunds go back to them.
+        /// All tickets of a participant come from this one payer.
+        payers: StorageHashMap<AccountId, AccountId>,
+        /// (winner, prize, claimed) per drawn slot, prizes are set on the
+        /// final draw and claimed once the winner withdraws them
+        winner_list: InkVec<(AccountId, Balance, bool)>,
         start_time: u64,
+        min_account_balance: Balance,
+        max_pot: Balance,
+        bonus_token: Option<AccountId>,
+        bonus_amount: Balance,
+        pending_bonus: StorageHashMap<AccountId, Balance>,
+        pending_withdrawals: StorageHashMap<AccountId, Balance>,
+        withdrawals_paused: bool,
+        draw_nonce: u32,
+        scheduled_open_ms: u64,
+        max_entries_per_block: u32,
+        last_entry_block: BlockNumber,
+        entries_in_block: u32,
+        target_block: BlockNumber,
+        /// (index, random, pool size, block) per drawn slot
+        draw_audit: InkVec<(u32, u32, u32, u64)>,
+        fee_bps: u16,
+        fee_recipient: AccountId,
+        paused: bool,
+        cancelled: bool,
+        weighted: bool,
+        trigger: u32,
+        duration_ms: u64,
+        stake_token: Option<AccountId>,
+        allow_receiver_entry: bool,
+        /// Lifetime stats, reset() leaves them alone
+        lifetime_paid: Balance,
+        rounds_completed: u32,
+        /// Count the countdown in blocks from start_block instead of time
+        block_countdown: bool,
+        start_block: BlockNumber,
+        duration_in_blocks: BlockNumber,
+        use_allowlist: bool,
+        allowlist: StorageHashMap<AccountId, bool>,
+        max_participants: u32,
+        refund_overpay: bool,
+        /// Bumped by reset(), tags every event with the round it belongs to
+        round_id: u32,
+        /// Set by close_entries(), ends the countdown early
+        entries_closed: bool,
+        /// Timestamp of the final draw, 0 while the round runs
+        finalized_at: u64,
+        claim_grace_ms: u64,
+        /// Set once sweep_unclaimed() moved the unclaimed prizes
+        prizes_swept: bool,
+        /// Tickets an account may hold, deposits sums them up
+        max_entries_per_account: u32,
+        entry_counts: StorageHashMap<AccountId, u32>,
+        /// Held while draw_winner() runs, the bonus token gets called from it
+        locked: bool,
+        /// Named by transfer_ownership(), becomes owner on accept_ownership()
+        pending_owner: Option<AccountId>,
+        /// Time every round gets to reach the trigger, 0 for no limit.
+        /// Taken from the constructor's entry_deadline.
+        entry_period_ms: u64,
+        /// Timestamp the current round has to reach the trigger by, 0 for
+        /// none. reset() starts a new entry period.
+        entry_deadline: u64,
     }
 
     /// Event emitted when new participant enters the raffle.
     #[ink(event)]
     pub struct NewParticipant {
+        #[ink(topic)]
+        round_id: u32,
         #[ink(topic)]
         participant: Option<AccountId>,
         #[ink(topic)]
         value: Balance,
+        /// Pool size including this participant
+        count: u32,
     }
 
     /// Event emitted when a winner is drawn.
     #[ink(event)]
     pub struct RaffleWinner {
+        #[ink(topic)]
+        round_id: u32,
         #[ink(topic)]
         winner: Option<AccountId>,
         #[ink(topic)]
         index: u32,
+        /// Value that picked the winner, the top 32 bits of the 128 bit
+        /// value a weighted draw uses
+        random: u32,
+        block: BlockNumber,
+        /// Prize slot filled by this draw, 0 for the first winner
+        slot: u32,
     }
 
     /// Event emitted when a winner is drawn.
     #[ink(event)]
     pub struct RaffleOpen {
+        #[ink(topic)]
+        round_id: u32,
         #[ink(topic)]
         time_remaining: u64,
     }
+
+    /// Event emitted when a participant leaves before the countdown.
+    #[ink(event)]
+    pub struct ParticipantLeft {
+        #[ink(topic)]
+        round_id: u32,
+        #[ink(topic)]
+        participant: Option<AccountId>,
+        #[ink(topic)]
+        value: Balance,
+    }
+
+    /// Event emitted when a payout leaves the contract through withdraw().
+    #[ink(event)]
+    pub struct PotTransferred {
+        #[ink(topic)]
+        round_id: u32,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        amount: Balance,
+    }
+
+    /// Event emitted when the owner points the pot at another account.
+    #[ink(event)]
+    pub struct PotReceiverChanged {
+        #[ink(topic)]
+        round_id: u32,
+        #[ink(topic)]
+        old: Option<AccountId>,
+        #[ink(topic)]
+        new: Option<AccountId>,
+    }
+
+    /// Event emitted when a pending owner accepts the raffle.
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        round_id: u32,
+        #[ink(topic)]
+        previous: Option<AccountId>,
+        #[ink(topic)]
+        new: Option<AccountId>,
+    }
+
+    /// Event emitted when the pool reaches the trigger and the countdown starts.
+    #[ink(event)]
+    pub struct RaffleTriggered {
+        #[ink(topic)]
+        round_id: u32,
+        start_time: u64,
+        /// First timestamp a draw is allowed at, None for a block countdown
+        deadline: Option<u64>,
+    }
     
+    /// Event emitted once per round, after the final winner is drawn.
+    #[ink(event)]
+    pub struct RaffleFinished {
+        #[ink(topic)]
+        round_id: u32,
+        /// Pot credited to winners, fee_recipient and pot_receiver
+        total_paid: Balance,
+        winner_count: u32,
+    }
+
     impl Raffle {
         #[ink(constructor)]
         pub fn new(pot_receiver: AccountId) -> Self {
             let instance = Self { 
+                owner: Self::env().caller(),
                 pot_receiver,
                 total_balance: 0 as Balance,
+                deposit_min: DEPOSIT_MIN,
+                deposit_max: DEPOSIT_MAX,
                 enough_participants: false,
                 winners: 0,
+                num_winners: RAFFLE_WINNERS,
                 participant_list: InkVec::new(),
-                winner_list: [None, None],
+                tickets: InkVec::new(),
+                deposits: StorageHashMap::new(),
+                joined_at: StorageHashMap::new(),
+                payers: StorageHashMap::new(),
+                winner_list: InkVec::new(),
                 start_time:  0,
+                min_account_balance: 0,
+                max_pot: 0,
+                bonus_token: None,
+                bonus_amount: 0,
+                pending_bonus: StorageHashMap::new(),
+                pending_withdrawals: StorageHashMap::new(),
+                withdrawals_paused: false,
+                draw_nonce: 0,
+                scheduled_open_ms: 0,
+                max_entries_per_block: 0,
+                last_entry_block: 0,
+                entries_in_block: 0,
+                target_block: 0,
+                draw_audit: InkVec::new(),
+                fee_bps: 0,
+                fee_recipient: pot_receiver,
+                paused: false,
+                cancelled: false,
+                weighted: false,
+                trigger: RAFFLE_TRIGGER,
+                duration_ms: DURATION_IN_MS,
+                stake_token: None,
+                allow_receiver_entry: true,
+                lifetime_paid: 0,
+                rounds_completed: 0,
+                block_countdown: false,
+                start_block: 0,
+                duration_in_blocks: 0,
+                use_allowlist: false,
+                allowlist: StorageHashMap::new(),
+                max_participants: 0,
+                refund_overpay: false,
+                round_id: 0,
+                entries_closed: false,
+                finalized_at: 0,
+                claim_grace_ms: CLAIM_GRACE_MS,
+                prizes_swept: false,
+                max_entries_per_account: 1,
+                entry_counts: StorageHashMap::new(),
+                locked: false,
+                pending_owner: None,
+                entry_period_ms: 0,
+                entry_deadline: 0,
              };
              instance
         }
 
+        /// Raffle with `winners` winners instead of the default 2, whose
+        /// countdown starts at `trigger` participants instead of RAFFLE_TRIGGER
+        /// and lasts `duration_ms`. At most `max_participants` can enter,
+        /// 0 means unlimited.
+        /// There must be at least one winner and no more than `trigger`,
+        /// a cap must leave room for `trigger` participants.
+        #[ink(constructor)]
+        pub fn new_with_config(
+            pot_receiver: AccountId,
+            winners: u32,
+            trigger: u32,
+            duration_ms: u64,
+            max_participants: u32,
+        ) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.trigger = trigger;
+            instance.duration_ms = duration_ms;
+            instance.max_participants = max_participants;
+            instance.num_winners = winners;
+            instance.validate_config().expect("invalid raffle config");
+            instance
+        }
+
+        /// Raffle accepting deposits between `deposit_min` and `deposit_max`
+        #[ink(constructor)]
+        pub fn new_with_limits(pot_receiver: AccountId, deposit_min: Balance, deposit_max: Balance) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.deposit_min = deposit_min;
+            instance.deposit_max = deposit_max;
+            instance.validate_config().expect("invalid raffle config");
+            instance
+        }
+
+        /// Anti-sybil variant: participants must hold at least
+        /// `min_account_balance` when they enter. Needs the
+        /// `min-account-balance` feature, deployment fails without it.
+        /// The contracts pallet can't read other accounts' balances, they
+        /// come from AccountBalances, see BalanceSource.
+        #[ink(constructor)]
+        pub fn new_with_min_account_balance(pot_receiver: AccountId, min_account_balance: Balance) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.min_account_balance = min_account_balance;
+            instance.validate_config().expect("invalid raffle config");
+            instance
+        }
+
+        /// Pot-capped variant: deposits are rejected once the pot would
+        /// exceed `max_pot`. Zero means no cap.
+        #[ink(constructor)]
+        pub fn new_with_max_pot(pot_receiver: AccountId, max_pot: Balance) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.max_pot = max_pot;
+            instance
+        }
+
+        /// Bonus variant: on top of the native pot, every winner gets
+        /// `bonus_amount` of the ERC20 `bonus_token` when the raffle ends.
+        /// The raffle contract must hold enough of the token.
+        #[ink(constructor)]
+        pub fn new_with_bonus(pot_receiver: AccountId, bonus_token: AccountId, bonus_amount: Balance) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.bonus_token = Some(bonus_token);
+            instance.bonus_amount = bonus_amount;
+            instance
+        }
+
+        /// Raffle announced in advance, entries open at `scheduled_open_ms`
+        #[ink(constructor)]
+        pub fn new_scheduled(pot_receiver: AccountId, scheduled_open_ms: u64) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.scheduled_open_ms = scheduled_open_ms;
+            instance
+        }
+
+        /// Spam protection, at most `max_entries_per_block` entries
+        /// are accepted in one block. Zero means no limit.
+        #[ink(constructor)]
+        pub fn new_rate_limited(pot_receiver: AccountId, max_entries_per_block: u32) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.max_entries_per_block = max_entries_per_block;
+            instance
+        }
+
+        /// Raffle run as a service, `fee_bps` of the pot goes to
+        /// `fee_recipient` before the winners are paid. At most MAX_FEE_BPS.
+        #[ink(constructor)]
+        pub fn new_with_fee(pot_receiver: AccountId, fee_recipient: AccountId, fee_bps: u16) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.fee_recipient = fee_recipient;
+            instance.fee_bps = fee_bps;
+            instance.validate_config().expect("invalid raffle config");
+            instance
+        }
+
+        /// With `weighted` set the odds of each participant follow
+        /// the size of their deposit instead of being equal
+        #[ink(constructor)]
+        pub fn new_weighted(pot_receiver: AccountId, weighted: bool) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.weighted = weighted;
+            instance
+        }
+
+        /// Raffle staked in the ERC20 at `stake_token` instead of the native
+        /// token. Entries go through participate_with_token(), deposit limits
+        /// and payouts are in token units.
+        #[ink(constructor)]
+        pub fn new_with_stake_token(pot_receiver: AccountId, stake_token: AccountId) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.stake_token = Some(stake_token);
+            instance
+        }
+
+        /// With `allow_receiver_entry` unset pot_receiver can't play,
+        /// it would get its stake back on top of the pot
+        #[ink(constructor)]
+        pub fn new_with_receiver_entry(pot_receiver: AccountId, allow_receiver_entry: bool) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.allow_receiver_entry = allow_receiver_entry;
+            instance
+        }
+
+        /// For chains with unreliable timestamps: with `block_countdown` set
+        /// draws open `duration_in_blocks` blocks after the trigger instead
+        /// of after the countdown duration
+        #[ink(constructor)]
+        pub fn new_with_block_countdown(
+            pot_receiver: AccountId,
+            block_countdown: bool,
+            duration_in_blocks: BlockNumber,
+        ) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.block_countdown = block_countdown;
+            instance.duration_in_blocks = duration_in_blocks;
+            instance.validate_config().expect("invalid raffle config");
+            instance
+        }
+
+        /// With `use_allowlist` set only accounts the owner allow()ed can enter
+        #[ink(constructor)]
+        pub fn new_with_allowlist(pot_receiver: AccountId, use_allowlist: bool) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.use_allowlist = use_allowlist;
+            instance
+        }
+
+        /// With `refund_overpay` set a payment above deposit_max enters with
+        /// deposit_max and the rest can be pulled back with withdraw()
+        #[ink(constructor)]
+        pub fn new_with_refund_overpay(pot_receiver: AccountId, refund_overpay: bool) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.refund_overpay = refund_overpay;
+            instance
+        }
+
+        /// Winners get `claim_grace_ms` after the final draw to withdraw()
+        /// before the owner can sweep their payouts to pot_receiver
+        #[ink(constructor)]
+        pub fn new_with_claim_grace(pot_receiver: AccountId, claim_grace_ms: u64) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.claim_grace_ms = claim_grace_ms;
+            instance
+        }
+
+        /// Raffle that expires if it hasn't reached the trigger by the
+        /// `entry_deadline` timestamp. Entries are turned down from then on
+        /// and anyone may call cancel_and_refund(). Rounds after a reset()
+        /// get as long as the first one had.
+        #[ink(constructor)]
+        pub fn new_with_entry_deadline(pot_receiver: AccountId, entry_deadline: u64) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.entry_deadline = entry_deadline;
+            instance.entry_period_ms = entry_deadline.saturating_sub(Self::env().block_timestamp());
+            instance
+        }
+
+        /// Every account can buy up to `max_entries_per_account` tickets,
+        /// each one a separate chance in the draw. A single account still
+        /// wins at most one prize.
+        #[ink(constructor)]
+        pub fn new_with_entry_limit(pot_receiver: AccountId, max_entries_per_account: u32) -> Self {
+            let mut instance = Self::new(pot_receiver);
+            instance.max_entries_per_account = max_entries_per_account;
+            instance.validate_config().expect("invalid raffle config");
+            instance
+        }
+
         /// A message that can be called on instantiated contracts.
         /// This one accepts new participant
         /// If amount is not within limits, it is rejected
@@ -112,256 +638,4908 @@ mod raffle {
             
             // self.env().caller() can be anyone willing to pay. 
             // contract stores entered participant address
+            let mut value = self.env().transferred_balance();
+
+            if self.stake_token.is_some() {
+                return Err(Error::TokenStakeOnly)
+            }
+            if value == 0 {
+                return Err(Error::NoPaymentReceived)
+            }
+            let mut excess = 0;
+            if self.refund_overpay && value > self.deposit_max {
+                excess = value - self.deposit_max;
+                value = self.deposit_max;
+            }
+            let total_balance = self.check_entry(participant, value)?;
+            self.record_entry(participant, value, total_balance);
+            if excess > 0 {
+                self.credit(self.env().caller(), excess);
+            }
+            Ok(())
+        }
+
+        /// Enroll `participants[i]` with `amounts[i]` in one call, at most
+        /// MAX_BATCH_SIZE of them. The payment must cover the sum, anything
+        /// above it can be pulled back with withdraw(). If any entry is
+        /// invalid nobody is enrolled. An account may appear as often as
+        /// max_entries_per_account allows, each time buys a ticket.
+        #[ink(message, payable)]
+        pub fn batch_participate(&mut self, participants: Vec<AccountId>, amounts: Vec<Balance>) -> Result<()> {
+            if self.stake_token.is_some() {
+                return Err(Error::TokenStakeOnly)
+            }
+            if participants.len() != amounts.len() || participants.len() as u32 > MAX_BATCH_SIZE {
+                return Err(Error::InvalidBatch)
+            }
+            let mut total_balance = self.total_balance;
+            // accounts the batch adds to the pool, repeats only add tickets
+            let mut new_accounts = 0;
+            for (i, (participant, amount)) in participants.iter().zip(amounts.iter()).enumerate() {
+                self.check_entry(*participant, *amount)?;
+                let earlier = participants[..i].iter().filter(|a| *a == participant).count() as u32;
+                self.check_entry_count(*participant, earlier)?;
+                if earlier == 0 && !self.is_participating(*participant) {
+                    new_accounts += 1;
+                }
+                total_balance = total_balance.checked_add(*amount).ok_or(Error::BalanceOverflow)?;
+            }
+            let sum = total_balance - self.total_balance;
             let value = self.env().transferred_balance();
+            if value < sum {
+                return Err(Error::EndowmentOutOfLimits)
+            }
+            if sum > self.remaining_pot_capacity() {
+                return Err(Error::PotCapReached)
+            }
+            if self.pool_full(new_accounts) {
+                return Err(Error::PoolFull)
+            }
+            if self.max_entries_per_block > 0
+                && self.entries_in_current_block() + participants.len() as u32 > self.max_entries_per_block
+            {
+                return Err(Error::RateLimited)
+            }
+
+            let mut total_balance = self.total_balance;
+            for (participant, amount) in participants.into_iter().zip(amounts.into_iter()) {
+                total_balance += amount;
+                self.record_entry(participant, amount, total_balance);
+            }
+            if value > sum {
+                let caller = self.env().caller();
+                self.credit(caller, value - sum);
+            }
+            Ok(())
+        }
+
+        /// Enter with `amount` of the stake token. The caller must have
+        /// approved the raffle to spend it, the stake is pulled through
+        /// the token's transfer_from.
+        #[ink(message)]
+        pub fn participate_with_token(&mut self, participant: AccountId, amount: Balance) -> Result<()> {
+            let token = self.stake_token.ok_or(Error::NativeStakeOnly)?;
+            let total_balance = self.check_entry(participant, amount)?;
+            let from = self.env().caller();
+            if !StakeToken::at(token).transfer_from(from, self.env().account_id(), amount) {
+                return Err(Error::TransferError)
+            }
+            self.record_entry(participant, amount, total_balance);
+            Ok(())
+        }
+
+        /// Would participate() accept `account` right now? The deposit is
+        /// only known at call time, any amount in deposit_limits() is assumed.
+        #[ink(message)]
+        pub fn can_participate(&self, account: AccountId) -> bool {
+            self.check_entry(account, self.deposit_min).is_ok()
+        }
+
+        /// Every reason to turn down an entry of `value`, the pot after it
+        /// otherwise. Doesn't touch storage.
+        fn check_entry(&self, participant: AccountId, value: Balance) -> Result<Balance> {
+            if self.paused {
+                return Err(Error::Paused)
+            }
+
+            if self.cancelled {
+                return Err(Error::RaffleCancelled)
+            }
+
+            if self.entries_closed {
+                return Err(Error::EntriesClosed)
+            }
+
+            if self.entry_period_ended() {
+                return Err(Error::EntryPeriodEnded)
+            }
             
-            if value < DEPOSIT_MIN || value > DEPOSIT_MAX {
+            if value < self.deposit_min || value > self.deposit_max {
                 return Err(Error::EndowmentOutOfLimits)
             }
             
-            if self.winners == RAFFLE_WINNERS {
+            if self.finished() {
                 return Err(Error::RaffleFinished)
             }
-            
-            if self.is_participating(participant) {
-                return Err(Error::AlreadyParticipating)
+
+            if !self.is_open() {
+                return Err(Error::NotYetOpen)
+            }
+
+            if !self.is_allowed(participant) {
+                return Err(Error::NotAllowed)
+            }
+
+            if !self.allow_receiver_entry && participant == self.pot_receiver {
+                return Err(Error::ReceiverCannotParticipate)
+            }
+
+            #[cfg(feature = "min-account-balance")]
+            {
+                if !self.meets_min_account_balance(participant) {
+                    return Err(Error::AccountBalanceTooLow)
+                }
+            }
+
+            self.check_entry_count(participant, 0)?;
+
+            // refunds go to one payer, so every ticket needs the same one
+            if let Some(payer) = self.payers.get(&participant) {
+                if *payer != self.env().caller() {
+                    return Err(Error::PayerMismatch)
+                }
+            }
+
+            // another ticket of someone in the pool doesn't grow it
+            if !self.is_participating(participant) && self.pool_full(1) {
+                return Err(Error::PoolFull)
+            }
+
+            if value > self.remaining_pot_capacity() {
+                return Err(Error::PotCapReached)
+            }
+
+            let total_balance = self.total_balance.checked_add(value).ok_or(Error::BalanceOverflow)?;
+
+            if self.max_entries_per_block > 0 && self.entries_in_current_block() >= self.max_entries_per_block {
+                return Err(Error::RateLimited)
+            }
+            Ok(total_balance)
+        }
+
+        /// No room for `entries` more participants under max_participants
+        /// Can `participant` take another ticket on top of the ones they
+        /// hold and `pending` more from the same batch?
+        fn check_entry_count(&self, participant: AccountId, pending: u32) -> Result<()> {
+            if self.entries_of(participant).saturating_add(pending) >= self.max_entries_per_account {
+                if self.max_entries_per_account == 1 {
+                    return Err(Error::AlreadyParticipating)
+                }
+                return Err(Error::EntryLimitReached)
+            }
+            Ok(())
+        }
+
+        fn pool_full(&self, entries: u32) -> bool {
+            self.max_participants > 0
+                && self.participant_list.len().saturating_add(entries) > self.max_participants
+        }
+
+        fn entries_in_current_block(&self) -> u32 {
+            if Self::env().block_number() == self.last_entry_block { self.entries_in_block } else { 0 }
+        }
+
+        fn record_entry(&mut self, participant: AccountId, value: Balance, total_balance: Balance) {
+            self.entries_in_block = self.entries_in_current_block() + 1;
+            self.last_entry_block = Self::env().block_number();
+
+            if !self.is_participating(participant) {
+                self.participant_list.push(participant);
             }
-            self.participant_list.push(participant);
-            self.total_balance += value;
+            self.tickets.push(participant);
+            let deposit = self.deposit_of(participant);
+            self.deposits.insert(participant, deposit + value);
+            self.entry_counts.insert(participant, self.entries_of(participant) + 1);
+            if !self.joined_at.contains_key(&participant) {
+                self.joined_at.insert(participant, Self::env().block_timestamp());
+                self.payers.insert(participant, self.env().caller());
+            }
+            self.total_balance = total_balance;
             self.env().emit_event(NewParticipant {
+                round_id: self.round_id,
                 participant: Some(participant),
                 value,
+                count: self.participant_list.len(),
             });
             ink_env::debug_println( "event NewParticipant");
-            if self.participant_list.len() as u32 == RAFFLE_TRIGGER{
+            // start_time is set once per round, later entries never move it
+            if !self.enough_participants && self.participant_list.len() as u32 >= self.trigger {
                 self.enough_participants = true;
                 self.start_time = Self::env().block_timestamp();
+                self.start_block = Self::env().block_number();
+                self.env().emit_event(RaffleTriggered {
+                    round_id: self.round_id,
+                    start_time: self.start_time,
+                    deadline: self.deadline(),
+                });
             }
-            Ok(())
-        }
-
-        /// Check if account already paid... test only
-        fn is_participating(&self, account: AccountId ) -> bool {
-            for a in self.participant_list.iter(){
-                if a == &account{
-                    return true
-                }
-            }
-            false
         }
 
-        /// Draw winner
+        /// Caller backs out before the countdown starts, whoever paid their
+        /// deposit can pull it with withdraw()
         #[ink(message)]
-        pub fn draw_winner(&mut self) -> Result<()> {
-            if self.winners == RAFFLE_WINNERS{
-                return Err(Error::RaffleFinished)
+        pub fn leave(&mut self) -> Result<()> {
+            if self.cancelled {
+                return Err(Error::RaffleCancelled)
             }
-            if !self.enough_participants{
-                return Err(Error::TooFewParticpants)
+            if self.enough_participants {
+                return Err(Error::CountdownStarted)
             }
-            if self.countdown_ongoing(){
-                return Err(Error::RaffleStillOpen)
+            let caller = self.env().caller();
+            let index = self
+                .participant_list
+                .iter()
+                .position(|a| *a == caller)
+                .ok_or(Error::NotParticipating)? as u32;
+
+            // shift the rest down so the entry order is kept
+            for i in index..self.participant_list.len() - 1 {
+                let next = *self.participant_list.get(i + 1).unwrap();
+                self.participant_list.set(i, next).unwrap();
             }
-            let winner_index: u32 = self.get_random_index();
-            let dbg_msg = format!( "random index {:#?}", winner_index );
-            ink_env::debug_println( &dbg_msg );
-            let winner = *self.participant_list.get(winner_index).unwrap();
-            
-            self.winner_list[self.winners as usize] = Some(winner);
-            self.winners += 1;
-            if self.winners == RAFFLE_WINNERS {
-                let result = self.transfer_pot();
-                if !result {
-                    return Err(Error::TransferError);
-                }
+            self.participant_list.pop();
+            // drop every ticket of the caller, the rest keep their order
+            let kept: Vec<AccountId> = self.tickets.iter().copied().filter(|a| *a != caller).collect();
+            for (i, account) in kept.iter().enumerate() {
+                self.tickets.set(i as u32, *account).unwrap();
             }
-            self.env().emit_event(RaffleWinner { winner: Some(winner), index: winner_index });
-            Ok(())
-        }  
-        
-        fn countdown_ongoing(&self) -> bool{
-            let time_diff = Self::env().block_timestamp() - self.start_time;
-            if time_diff < DURATION_IN_MS{
-                self.env().emit_event(RaffleOpen {time_remaining: time_diff });
-                ink_env::debug_println( "event RaffleOpen");
-                return true;
+            while self.tickets.len() > kept.len() as u32 {
+                self.tickets.pop();
             }
-            false
+            self.entry_counts.take(&caller);
+            let deposit = self.deposits.take(&caller).unwrap_or(0);
+            self.joined_at.take(&caller);
+            let payer = self.payers.take(&caller).unwrap_or(caller);
+            self.total_balance -= deposit;
+            self.credit(payer, deposit);
+            self.env().emit_event(ParticipantLeft {
+                round_id: self.round_id,
+                participant: Some(caller),
+                value: deposit,
+            });
+            Ok(())
         }
 
-        fn transfer_pot(&mut self) -> bool{
-            let result = self.env().transfer(self.pot_receiver, self.total_balance);
-            if result == Ok(()) {
-                return true;
+        /// An unknown balance counts as too low
+        #[cfg(feature = "min-account-balance")]
+        fn meets_min_account_balance(&self, account: AccountId) -> bool {
+            if self.min_account_balance == 0 {
+                return true
+            }
+            match AccountBalances::free_balance(account) {
+                Some(balance) => balance >= self.min_account_balance,
+                None => false,
             }
-            false
         }
 
-        fn get_random_index(&self) -> u32 {
-            let random_index: u32 = Self::get_random_number();
-            random_index % self.participant_list.len()
+        /// Constructors panic on an inconsistent setup, reverting the deploy
+        fn validate_config(&self) -> Result<()> {
+            if self.num_winners == 0 || self.num_winners > self.trigger {
+                return Err(Error::InvalidConfig)
+            }
+            if self.deposit_min == 0 || self.deposit_min >= self.deposit_max {
+                return Err(Error::InvalidConfig)
+            }
+            if self.duration_ms == 0 {
+                return Err(Error::InvalidConfig)
+            }
+            if self.block_countdown && self.duration_in_blocks == 0 {
+                return Err(Error::InvalidConfig)
+            }
+            if self.max_participants != 0 && self.max_participants < self.trigger {
+                return Err(Error::InvalidConfig)
+            }
+            if self.fee_bps > MAX_FEE_BPS {
+                return Err(Error::InvalidConfig)
+            }
+            // weights come from deposits, extra tickets would count them twice
+            // since weighted_index walks the tickets
+            if self.max_entries_per_account == 0 || (self.weighted && self.max_entries_per_account > 1) {
+                return Err(Error::InvalidConfig)
+            }
+            // without the feature nothing would enforce the minimum
+            if cfg!(not(feature = "min-account-balance")) && self.min_account_balance > 0 {
+                return Err(Error::InvalidConfig)
+            }
+            Ok(())
         }
-        
-        /// Check number of participants
+
+        /// Deposit window as (min, max)
         #[ink(message)]
-        pub fn participants(&self) -> u32 {
-            self.participant_list.len() 
+        pub fn deposit_limits(&self) -> (Balance, Balance) {
+            (self.deposit_min, self.deposit_max)
         }
-        
-        /// Check raffle balance
+
+        /// Every participant has an entry in deposits, O(1) instead of a scan
+        fn is_participating(&self, account: AccountId ) -> bool {
+            self.deposits.contains_key(&account)
+        }
+
+        /// Tickets `account` holds in the current round
         #[ink(message)]
-        pub fn total_balance(&self) -> u128 {
-            self.total_balance
+        pub fn entries_of(&self, account: AccountId) -> u32 {
+            self.entry_counts.get(&account).copied().unwrap_or(0)
         }
 
-        /// Winner list
+        /// Chance of `account` taking the next draw in basis points: its
+        /// deposit over those of everyone who hasn't won yet when weighted,
+        /// its tickets over theirs otherwise. 0 once it won.
+        /// After a win the equal draw moves from a winner's ticket to the
+        /// next one, so later draws only approximate this.
         #[ink(message)]
-        pub fn winner_address(&self) -> [Option<AccountId>; RAFFLE_WINNERS as usize] {
-            self.winner_list
+        pub fn win_probability_bps(&self, account: AccountId) -> u32 {
+            if !self.is_participating(account) || self.is_winner(account) {
+                return 0
+            }
+            let (mine, total): (Balance, Balance) = if self.weighted {
+                let remaining = self
+                    .participant_list
+                    .iter()
+                    .filter(|a| !self.is_winner(**a))
+                    .map(|a| self.deposit_of(*a))
+                    .sum();
+                (self.deposit_of(account), remaining)
+            } else {
+                let remaining = self.tickets.iter().filter(|a| !self.is_winner(**a)).count();
+                (self.entries_of(account) as Balance, remaining as Balance)
+            };
+            (mine.saturating_mul(10_000) / total) as u32
         }
 
-        /// Is Raffle over?
+        /// Draw winner, owner only
         #[ink(message)]
-        pub fn finished(&self) -> bool{
-            self.winners == RAFFLE_WINNERS
+        pub fn draw_winner(&mut self) -> Result<()> {
+            if self.locked {
+                return Err(Error::Reentrancy)
+            }
+            self.locked = true;
+            let result = self.draw_winner_locked();
+            self.locked = false;
+            result
+        }
+
+        fn draw_winner_locked(&mut self) -> Result<()> {
+            self.check_draw(1)?;
+            self.draw_one()?;
+            if self.finished() {
+                self.finish_round();
+            }
+            Ok(())
+        }
+
+        /// Draw all remaining winners at once and pay out, owner only
+        #[ink(message)]
+        pub fn draw_all(&mut self) -> Result<()> {
+            if self.locked {
+                return Err(Error::Reentrancy)
+            }
+            self.locked = true;
+            let result = self.draw_all_locked();
+            self.locked = false;
+            result
+        }
+
+        fn draw_all_locked(&mut self) -> Result<()> {
+            let remaining = self.num_winners.saturating_sub(self.winners);
+            self.check_draw(remaining)?;
+            for _ in 0..remaining {
+                self.draw_one()?;
+            }
+            self.finish_round();
+            Ok(())
+        }
+
+        /// Checks shared by draw_winner and draw_all before drawing `count`
+        /// more winners
+        fn check_draw(&mut self, count: u32) -> Result<()> {
+            self.ensure_owner()?;
+            if self.cancelled {
+                return Err(Error::RaffleCancelled)
+            }
+            if self.finished() {
+                return Err(Error::RaffleFinished)
+            }
+            if !self.enough_participants{
+                return Err(Error::TooFewParticpants)
+            }
+            let drawn = self.winners.checked_add(count).ok_or(Error::RaffleFinished)?;
+            // someone who hasn't won yet must be left for every draw
+            if self.participant_list.len() < drawn {
+                return Err(Error::TooFewParticpants)
+            }
+            if self.countdown_ongoing(){
+                if !self.block_countdown {
+                    let time_remaining = self.remaining_time();
+                    self.env().emit_event(RaffleOpen {round_id: self.round_id, time_remaining });
+                    ink_env::debug_println( "event RaffleOpen");
+                }
+                return Err(Error::RaffleStillOpen)
+            }
+            if self.target_block > 0 && Self::env().block_number() <= self.target_block {
+                return Err(Error::TargetBlockNotReached)
+            }
+            // the last draw credits pot_receiver and fee_recipient, nobody
+            // could withdraw for the zero account
+            let zero = AccountId::from([0x0; 32]);
+            if drawn >= self.num_winners
+                && (self.pot_receiver == zero || (self.fee_bps > 0 && self.fee_recipient == zero))
+            {
+                return Err(Error::InvalidConfig)
+            }
+            Ok(())
+        }
+
+        /// Pick and record one winner, check_draw must have passed
+        fn draw_one(&mut self) -> Result<()> {
+            let drawn = self.winners.checked_add(1).ok_or(Error::RaffleFinished)?;
+            let (winner_index, random) = self.pick_winner_index();
+            self.draw_nonce = self.draw_nonce.wrapping_add(1);
+            let dbg_msg = format!( "random index {:#?}", winner_index );
+            ink_env::debug_println( &dbg_msg );
+            let winner = *self.tickets.get(winner_index).unwrap();
+            
+            self.winner_list.push((winner, 0, false));
+            self.draw_audit.push((
+                winner_index,
+                random,
+                self.tickets.len(),
+                Self::env().block_number() as u64,
+            ));
+            let slot = self.winners;
+            self.winners = drawn;
+            self.env().emit_event(RaffleWinner {
+                round_id: self.round_id,
+                winner: Some(winner),
+                index: winner_index,
+                random,
+                block: Self::env().block_number(),
+                slot,
+            });
+            Ok(())
+        }
+
+        /// Pay out once the last winner is drawn
+        fn finish_round(&mut self) {
+            self.credit_pot();
+            self.pay_bonus();
+            self.lifetime_paid = self.lifetime_paid.saturating_add(self.total_balance);
+            self.rounds_completed += 1;
+            self.finalized_at = Self::env().block_timestamp();
+            self.env().emit_event(RaffleFinished {
+                round_id: self.round_id,
+                total_paid: self.total_balance,
+                winner_count: self.winners,
+            });
+        }
+
+        /// (index, random) the draw would pick now. random is the value
+        /// that picked the index after any rejection sampling redraws.
+        /// A weighted draw picks with get_wide_random_number(), random is
+        /// its top 32 bits. Both take a single value from the random source.
+        fn pick_winner_index(&self) -> (u32, u32) {
+            if self.weighted {
+                let wide = self.get_wide_random_number();
+                (self.weighted_index(wide), (wide >> 96) as u32)
+            } else {
+                let (index, random) = self.get_random_index(self.get_random_number());
+                (self.next_non_winner(index), random)
+            }
+        }
+
+        /// Dry run of draw_winner with the current state, no storage
+        /// changes and no events. Non-binding, the random subject changes
+        /// with every block. None before the trigger and once nobody is left.
+        #[ink(message)]
+        pub fn next_winner_preview(&self) -> Option<AccountId> {
+            if self.cancelled || self.finished() || !self.enough_participants {
+                return None
+            }
+            if self.participant_list.len() <= self.winners {
+                return None
+            }
+            let (index, _) = self.pick_winner_index();
+            self.tickets.get(index).copied()
+        }
+
+        /// Owner only, once the countdown started: draws must wait until
+        /// the chain is past `block`, whose number is mixed into the seed.
+        /// Nobody knows that block's randomness when it is designated.
+        #[ink(message)]
+        pub fn set_target_block(&mut self, block: BlockNumber) -> Result<()> {
+            self.ensure_owner()?;
+            if !self.enough_participants {
+                return Err(Error::TooFewParticpants)
+            }
+            if self.winners > 0 || block <= Self::env().block_number() {
+                return Err(Error::InvalidTargetBlock)
+            }
+            self.target_block = block;
+            Ok(())
+        }
+
+        /// Block the draw waits for, 0 if none
+        #[ink(message)]
+        pub fn target_block(&self) -> BlockNumber {
+            self.target_block
+        }
+
+        /// Raffle stage for frontends
+        #[ink(message)]
+        pub fn status(&self) -> RaffleStatus {
+            if self.finished() || self.cancelled {
+                return RaffleStatus::Completed
+            }
+            if !self.enough_participants {
+                return RaffleStatus::AwaitingParticipants
+            }
+            if !self.countdown_elapsed() {
+                return RaffleStatus::CountdownActive
+            }
+            RaffleStatus::ReadyToDraw
+        }
+
+        /// Milliseconds until a draw is possible, 0 once the countdown elapsed
+        /// u64::MAX while the raffle still waits for enough participants.
+        /// A block countdown has no time left, only 0 or u64::MAX.
+        #[ink(message)]
+        pub fn remaining_time(&self) -> u64 {
+            if !self.enough_participants {
+                return u64::MAX
+            }
+            if self.entries_closed {
+                return 0
+            }
+            if self.block_countdown {
+                return if self.countdown_elapsed() { 0 } else { u64::MAX }
+            }
+            let elapsed = Self::env().block_timestamp().saturating_sub(self.start_time);
+            self.duration_ms.saturating_sub(elapsed)
+        }
+
+        /// Timestamp from which a draw is allowed, None before the trigger
+        /// and for a block countdown
+        #[ink(message)]
+        pub fn deadline(&self) -> Option<u64> {
+            if !self.enough_participants || self.block_countdown {
+                return None
+            }
+            Some(self.start_time.saturating_add(self.duration_ms))
+        }
+
+        /// Participants still needed to start the countdown, 0 once it started
+        #[ink(message)]
+        pub fn participants_until_trigger(&self) -> u32 {
+            if self.enough_participants {
+                return 0
+            }
+            self.trigger.saturating_sub(self.participant_list.len())
+        }
+
+        /// Blocks until draw_winner() is allowed, 0 once it is and u32::MAX
+        /// while the raffle waits for enough participants. A time countdown
+        /// has no block count, it is u32::MAX until it elapsed.
+        #[ink(message)]
+        pub fn blocks_until_draw(&self) -> BlockNumber {
+            if !self.enough_participants {
+                return BlockNumber::MAX
+            }
+            let block = Self::env().block_number();
+            let countdown = if self.entries_closed {
+                0
+            } else if self.block_countdown {
+                let elapsed = block.saturating_sub(self.start_block);
+                self.duration_in_blocks.saturating_sub(elapsed)
+            } else if self.countdown_elapsed() {
+                0
+            } else {
+                return BlockNumber::MAX
+            };
+            // draws wait for the block after target_block
+            let target = if self.target_block > 0 {
+                self.target_block.saturating_add(1).saturating_sub(block)
+            } else {
+                0
+            };
+            countdown.max(target)
+        }
+
+        /// Countdown started and ran out, whichever way it is counted,
+        /// or the owner closed entries
+        fn countdown_elapsed(&self) -> bool {
+            if !self.enough_participants {
+                return false
+            }
+            if self.entries_closed {
+                return true
+            }
+            if self.block_countdown {
+                let blocks = Self::env().block_number().saturating_sub(self.start_block);
+                return blocks >= self.duration_in_blocks
+            }
+            self.start_time != 0 && self.remaining_time() == 0
+        }
+
+        /// Test helper, shortens a running countdown to the time (or blocks)
+        /// already passed so a draw is allowed right away. Does nothing
+        /// before the trigger.
+        #[cfg(test)]
+        pub fn force_expire(&mut self) {
+            if !self.enough_participants {
+                return
+            }
+            if self.block_countdown {
+                self.duration_in_blocks = Self::env().block_number().saturating_sub(self.start_block);
+            } else {
+                self.duration_ms = Self::env().block_timestamp().saturating_sub(self.start_time);
+            }
+        }
+
+        /// Countdown triggered and still running, no side effects
+        fn countdown_ongoing(&self) -> bool{
+            self.enough_participants && !self.countdown_elapsed()
+        }
+
+        /// Draws never send funds, the pot is credited for withdraw().
+        /// The house fee comes off first, each winner gets an equal share
+        /// of the rest and the dust goes to pot_receiver.
+        fn credit_pot(&mut self) {
+            let fee = self.house_fee();
+            if fee > 0 {
+                self.credit(self.fee_recipient, fee);
+            }
+            let prize = self.total_balance - fee;
+            let count = self.winner_list.len() as Balance;
+            let share = prize / count;
+            for slot in 0..self.winner_list.len() {
+                let (winner, _, _) = *self.winner_list.get(slot).unwrap();
+                self.winner_list.set(slot, (winner, share, false)).unwrap();
+                self.credit(winner, share);
+            }
+            let dust = prize - share * count;
+            if dust > 0 {
+                self.credit(self.pot_receiver, dust);
+            }
+        }
+
+        /// fee_bps of the pot, rounded down. Split at 10_000 so a pot
+        /// near Balance::MAX can't overflow the multiplication.
+        fn house_fee(&self) -> Balance {
+            let bps = self.fee_bps as Balance;
+            self.total_balance / 10_000 * bps + self.total_balance % 10_000 * bps / 10_000
+        }
+
+        /// House fee as (fee_bps, fee_recipient)
+        #[ink(message)]
+        pub fn fee(&self) -> (u16, AccountId) {
+            (self.fee_bps, self.fee_recipient)
+        }
+
+        fn credit(&mut self, account: AccountId, amount: Balance) {
+            let pending = self.pending_withdrawal(account);
+            self.pending_withdrawals.insert(account, pending + amount);
+        }
+
+        /// Pull the caller's payout once the raffle is finished
+        #[ink(message)]
+        pub fn withdraw(&mut self) -> Result<()> {
+            if self.withdrawals_paused {
+                return Err(Error::WithdrawalsPaused)
+            }
+            let caller = self.env().caller();
+            let amount = self.pending_withdrawals.take(&caller).unwrap_or(0);
+            if amount == 0 {
+                return Err(Error::NothingToWithdraw)
+            }
+            let paid = match self.stake_token {
+                Some(token) => StakeToken::at(token).transfer(caller, amount),
+                None => self.env().transfer(caller, amount).is_ok(),
+            };
+            if !paid {
+                self.pending_withdrawals.insert(caller, amount);
+                return Err(Error::TransferError)
+            }
+            if self.finished() {
+                self.mark_claimed(caller);
+            }
+            self.env().emit_event(PotTransferred {
+                round_id: self.round_id,
+                to: Some(caller),
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Flag the prize of `account` as paid out, if it won this round
+        fn mark_claimed(&mut self, account: AccountId) {
+            for slot in 0..self.winner_list.len() {
+                let (winner, prize, _) = *self.winner_list.get(slot).unwrap();
+                if winner == account {
+                    self.winner_list.set(slot, (winner, prize, true)).unwrap();
+                }
+            }
+        }
+
+        /// Owner only, once claim_grace_ms passed since the final draw the
+        /// prizes winners haven't withdrawn are credited to pot_receiver.
+        /// Fees, refunds and anything else owed stay with their owners.
+        #[ink(message)]
+        pub fn sweep_unclaimed(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            if self.finalized_at == 0 {
+                return Err(Error::RaffleInProgress)
+            }
+            let elapsed = Self::env().block_timestamp().saturating_sub(self.finalized_at);
+            if elapsed < self.claim_grace_ms {
+                return Err(Error::GracePeriodActive)
+            }
+            if self.prizes_swept {
+                return Err(Error::NothingToWithdraw)
+            }
+            let unclaimed: Vec<(AccountId, Balance)> = self
+                .winner_list
+                .iter()
+                .filter(|(winner, _, claimed)| !claimed && *winner != self.pot_receiver)
+                .map(|(winner, prize, _)| (*winner, *prize))
+                .collect();
+            let mut swept = 0;
+            for (winner, prize) in unclaimed {
+                let pending = self.pending_withdrawal(winner);
+                let amount = prize.min(pending);
+                self.pending_withdrawals.insert(winner, pending - amount);
+                swept += amount;
+            }
+            if swept == 0 {
+                return Err(Error::NothingToWithdraw)
+            }
+            self.prizes_swept = true;
+            self.credit(self.pot_receiver, swept);
+            Ok(())
+        }
+
+        /// Owner only, emergency stop for participate(). Draws still work
+        /// so a stuck raffle can be finalized.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.paused = true;
+            Ok(())
+        }
+
+        /// Owner only, accept entries again
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.paused = false;
+            Ok(())
+        }
+
+        /// Are entries frozen?
+        #[ink(message)]
+        pub fn paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Owner only, put `account` on the allowlist
+        #[ink(message)]
+        pub fn allow(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            self.allowlist.insert(account, true);
+            Ok(())
+        }
+
+        /// Owner only, take `account` off the allowlist
+        #[ink(message)]
+        pub fn disallow(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            self.allowlist.insert(account, false);
+            Ok(())
+        }
+
+        /// May `account` enter? Always true without an allowlist
+        #[ink(message)]
+        pub fn is_allowed(&self, account: AccountId) -> bool {
+            !self.use_allowlist || self.allowlist.get(&account).copied().unwrap_or(false)
+        }
+
+        /// Owner only, freeze withdraw() and claim_bonus()
+        #[ink(message)]
+        pub fn pause_withdrawals(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.withdrawals_paused = true;
+            Ok(())
+        }
+
+        /// Owner only, release withdraw() and claim_bonus()
+        #[ink(message)]
+        pub fn unpause_withdrawals(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.withdrawals_paused = false;
+            Ok(())
+        }
+
+        /// Are payouts frozen?
+        #[ink(message)]
+        pub fn withdrawals_paused(&self) -> bool {
+            self.withdrawals_paused
+        }
+
+        /// Account that deployed the raffle, or accepted it since
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Owner only, offer the raffle to `new_owner`. Nothing changes
+        /// until they call accept_ownership(), a new offer replaces this one.
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            self.pending_owner = Some(new_owner);
+            Ok(())
+        }
+
+        /// Pending owner takes over the raffle
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_owner != Some(caller) {
+                return Err(Error::NotPendingOwner)
+            }
+            let previous = self.owner;
+            self.owner = caller;
+            self.pending_owner = None;
+            self.env().emit_event(OwnershipTransferred {
+                round_id: self.round_id,
+                previous: Some(previous),
+                new: Some(caller),
+            });
+            Ok(())
+        }
+
+        /// Account offered ownership, None if no transfer is pending
+        #[ink(message)]
+        pub fn pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Account credited with the pot's dust on the final draw
+        #[ink(message)]
+        pub fn pot_receiver(&self) -> AccountId {
+            self.pot_receiver
+        }
+
+        /// Owner only, fix a mistyped or compromised pot_receiver
+        /// before the final draw credits it
+        #[ink(message)]
+        pub fn set_pot_receiver(&mut self, new: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            if self.finished() {
+                return Err(Error::RaffleFinished)
+            }
+            let old = self.pot_receiver;
+            self.pot_receiver = new;
+            self.env().emit_event(PotReceiverChanged {
+                round_id: self.round_id,
+                old: Some(old),
+                new: Some(new),
+            });
+            Ok(())
+        }
+
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            Ok(())
+        }
+
+        /// Native balance waiting to be withdrawn by `account`
+        #[ink(message)]
+        pub fn pending_withdrawal(&self, account: AccountId) -> Balance {
+            self.pending_withdrawals.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Send the bonus token to every winner
+        /// Failed transfers are kept in pending_bonus for claim_bonus()
+        /// ink writes storage back only when a message returns, so without
+        /// this a token calling back in during pay_bonus would load the
+        /// state from before the call, lock released.
+        fn flush(&self) {
+            push_spread_root::<Self>(self, &Key::from([0x00; 32]));
+        }
+
+        fn pay_bonus(&mut self) {
+            let token = match self.bonus_token {
+                Some(token) => token,
+                None => return,
+            };
+            self.flush();
+            for winner in self.winners_in_draw_order() {
+                if !StakeToken::at(token).transfer(winner, self.bonus_amount) {
+                    let pending = self.pending_bonus(winner);
+                    self.pending_bonus.insert(winner, pending + self.bonus_amount);
+                }
+            }
+        }
+
+        /// Winner pulls a bonus whose transfer failed at the end of the raffle
+        #[ink(message)]
+        pub fn claim_bonus(&mut self) -> Result<()> {
+            if self.withdrawals_paused {
+                return Err(Error::WithdrawalsPaused)
+            }
+            let caller = self.env().caller();
+            let amount = self.pending_bonus.take(&caller).unwrap_or(0);
+            let token = match self.bonus_token {
+                Some(token) if amount > 0 => token,
+                _ => return Err(Error::NothingToWithdraw),
+            };
+            if !StakeToken::at(token).transfer(caller, amount) {
+                self.pending_bonus.insert(caller, amount);
+                return Err(Error::TransferError)
+            }
+            Ok(())
+        }
+
+        /// Bonus tokens waiting to be claimed by `account`
+        #[ink(message)]
+        pub fn pending_bonus(&self, account: AccountId) -> Balance {
+            self.pending_bonus.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Stake token held by the raffle as the token reports it,
+        /// 0 for a native raffle
+        #[ink(message)]
+        pub fn stake_token_balance(&self) -> Balance {
+            match self.stake_token {
+                Some(token) => StakeToken::at(token).balance_of(self.env().account_id()),
+                None => 0,
+            }
+        }
+
+        /// (ticket index, random value it was taken from)
+        fn get_random_index(&self, random: u32) -> (u32, u32) {
+            let mut attempt = 0;
+            Self::unbiased_index(random, self.tickets.len(), || {
+                attempt += 1;
+                self.get_random_number_at(attempt)
+            })
+        }
+
+        /// Rejection sampling, `random % len` alone favours low indices
+        /// when len doesn't divide 2^32. Raw values above the largest
+        /// multiple of len that fits in a u32 are redrawn.
+        /// Returns (random % len, the random value that was kept).
+        fn unbiased_index(mut random: u32, len: u32, mut redraw: impl FnMut() -> u32) -> (u32, u32) {
+            // 2^32 % len, without leaving u32
+            let rem = (u32::MAX % len + 1) % len;
+            let limit = u32::MAX - rem;
+            while random > limit {
+                random = redraw();
+            }
+            (random % len, random)
+        }
+
+        /// Walk the cumulative deposits of those who haven't won yet
+        /// against `random % their total`, bigger deposits cover more of
+        /// the range. The 128 bit random keeps the modulo bias negligible.
+        fn weighted_index(&self, random: u128) -> u32 {
+            let has_won = |account: &AccountId| self.is_winner(*account);
+            let remaining: Balance = self
+                .tickets
+                .iter()
+                .filter(|a| !has_won(a))
+                .map(|a| self.deposit_of(*a))
+                .sum();
+            let mut target = random % remaining;
+            for (index, account) in self.tickets.iter().enumerate() {
+                if has_won(account) {
+                    continue
+                }
+                let deposit = self.deposit_of(*account);
+                if target < deposit {
+                    return index as u32
+                }
+                target -= deposit;
+            }
+            0
+        }
+
+        /// First index from `index` on, wrapping around, whose participant
+        /// has not won yet, so nobody takes two prizes
+        fn next_non_winner(&self, index: u32) -> u32 {
+            let len = self.tickets.len();
+            let mut candidate = index;
+            for _ in 0..len {
+                let account = *self.tickets.get(candidate).unwrap();
+                if !self.is_winner(account) {
+                    return candidate
+                }
+                candidate = (candidate + 1) % len;
+            }
+            index
         }
         
-        // Thanks to @LaurentTrk#4763 on discord for get_random_number()
-        // I wouldn't make on time without this
-        // It is up to polkadot-hello-world-jury to decide if my submission is legit
-        fn get_random_number() -> u32 {
-            let seed: [u8; 8] = [7, 8, 9, 10, 11, 12, 13, 14];
-            let random_hash = Self::env().random(&seed);
-            Self::as_u32_be(&random_hash.as_ref())
+        /// Check number of participants
+        #[ink(message)]
+        pub fn participants(&self) -> u32 {
+            self.participant_list.len() 
+        }
+        
+        /// Time from which participate() is accepted
+        #[ink(message)]
+        pub fn scheduled_open(&self) -> u64 {
+            self.scheduled_open_ms
+        }
+
+        /// Has the scheduled opening time been reached?
+        #[ink(message)]
+        pub fn is_open(&self) -> bool {
+            Self::env().block_timestamp() >= self.scheduled_open_ms
+        }
+
+        /// Amount `account` paid into the current round, 0 if not entered
+        #[ink(message)]
+        pub fn deposit_of(&self, account: AccountId) -> Balance {
+            self.deposits.get(&account).copied().unwrap_or(0)
+        }
+
+        /// Index of `account` in the pool, join order, None if not entered.
+        /// Members are found in O(1), only their index needs a scan.
+        #[ink(message)]
+        pub fn position_of(&self, account: AccountId) -> Option<u32> {
+            if !self.is_participating(account) {
+                return None
+            }
+            self.participant_list.iter().position(|a| *a == account).map(|index| index as u32)
+        }
+
+        /// Caller's entry as (index, deposit, joined_at), None if not entered
+        #[ink(message)]
+        pub fn my_receipt(&self) -> Option<(u32, Balance, u64)> {
+            let caller = self.env().caller();
+            let index = self.position_of(caller)?;
+            let deposit = self.deposit_of(caller);
+            let joined_at = self.joined_at.get(&caller).copied().unwrap_or(0);
+            Some((index, deposit, joined_at))
+        }
+
+        /// Tickets the draw picks from, in index order, an account with
+        /// several tickets appears several times
+        /// With the seed from RaffleWinner a draw can be checked off-chain
+        #[ink(message)]
+        pub fn draw_candidates(&self) -> Vec<AccountId> {
+            self.tickets.iter().copied().collect()
+        }
+
+        /// Everyone in the pool, in the order they joined
+        #[ink(message)]
+        pub fn get_participants(&self) -> Vec<AccountId> {
+            self.participant_list.iter().copied().collect()
+        }
+
+        /// Participants whose deposit lies within [min, max]
+        #[ink(message)]
+        pub fn participants_in_range(&self, min: Balance, max: Balance) -> Vec<AccountId> {
+            self.participant_list
+                .iter()
+                .filter(|a| {
+                    let deposit = self.deposits.get(*a).copied().unwrap_or(0);
+                    deposit >= min && deposit <= max
+                })
+                .copied()
+                .collect()
+        }
+
+        /// How much more can be deposited before max_pot is reached
+        /// Balance::MAX if the pot is not capped
+        #[ink(message)]
+        pub fn remaining_pot_capacity(&self) -> Balance {
+            if self.max_pot == 0 {
+                return Balance::MAX
+            }
+            self.max_pot.saturating_sub(self.total_balance)
+        }
+
+        /// Estimated number of average-sized deposits still needed
+        /// to bring the pot up to `target`
+        /// With no participants yet, deposit_min is taken as the average
+        #[ink(message)]
+        pub fn entries_to_reach(&self, target: Balance) -> u32 {
+            let missing = target.saturating_sub(self.total_balance);
+            if missing == 0 {
+                return 0
+            }
+            let average = match self.participant_list.len() {
+                0 => self.deposit_min,
+                count => self.total_balance / count as Balance,
+            };
+            let mut entries = missing / average;
+            if missing % average != 0 {
+                entries += 1;
+            }
+            if entries > u32::MAX as Balance {
+                return u32::MAX
+            }
+            entries as u32
+        }
+
+        /// Largest batch size clients should send in one call
+        #[ink(message)]
+        pub fn max_batch_size(&self) -> u32 {
+            MAX_BATCH_SIZE
+        }
+
+        /// Check raffle balance
+        #[ink(message)]
+        pub fn total_balance(&self) -> u128 {
+            self.total_balance
+        }
+
+        /// Everything a dashboard polls, read at once
+        #[ink(message)]
+        pub fn snapshot(&self) -> RaffleSnapshot {
+            RaffleSnapshot {
+                participant_count: self.participant_list.len(),
+                total_balance: self.total_balance,
+                winners_drawn: self.winners,
+                start_time: self.start_time,
+                remaining_time: self.remaining_time(),
+                status: self.status(),
+            }
+        }
+
+        /// Winner list
+        #[ink(message)]
+        pub fn winner_address(&self) -> Vec<Option<AccountId>> {
+            (0..self.num_winners).map(|slot| self.winner_at(slot)).collect()
+        }
+
+        /// Winner drawn into `slot`, None if undrawn or out of range
+        #[ink(message)]
+        pub fn winner_at(&self, slot: u32) -> Option<AccountId> {
+            self.winner_list.get(slot).map(|(winner, _, _)| *winner)
+        }
+
+        /// Everything needed to reproduce the draw of `slot`:
+        /// (winner, index, random, pool size, block)
+        /// In an equal draw the winner is draw_candidates()[random % pool size],
+        /// moved forward past anyone who already won. random is the value
+        /// kept after any rejection sampling redraws. A weighted draw walks
+        /// the deposits with a 128 bit value instead, see weighted_index(),
+        /// and random holds only its top 32 bits.
+        #[ink(message)]
+        pub fn winner_audit(&self, slot: u32) -> Option<(AccountId, u32, u32, u32, u64)> {
+            let winner = self.winner_at(slot)?;
+            let (index, random, pool_size, block) = *self.draw_audit.get(slot)?;
+            Some((winner, index, random, pool_size, block))
+        }
+
+        /// Was `account` drawn in the current round?
+        #[ink(message)]
+        pub fn is_winner(&self, account: AccountId) -> bool {
+            self.winner_list.iter().any(|(winner, _, _)| *winner == account)
+        }
+
+        /// Drawn winners, in the order they were selected
+        #[ink(message)]
+        pub fn winners_in_draw_order(&self) -> Vec<AccountId> {
+            self.winner_list.iter().map(|(winner, _, _)| *winner).collect()
+        }
+
+        /// Only the winners drawn so far, unlike winner_address()
+        /// there are no None slots
+        #[ink(message)]
+        pub fn get_winners(&self) -> Vec<AccountId> {
+            self.winners_in_draw_order()
+        }
+
+        /// Drawn winners with the prize credited to each, in draw order.
+        /// Prizes read 0 until the final draw splits the pot.
+        #[ink(message)]
+        pub fn winner_prizes(&self) -> Vec<(AccountId, Balance)> {
+            self.winner_list.iter().map(|(winner, prize, _)| (*winner, *prize)).collect()
+        }
+
+        /// Drawn winners as (winner, prize, claimed), in draw order.
+        /// claimed turns true once the winner withdrew after the final draw.
+        #[ink(message)]
+        pub fn winner_records(&self) -> Vec<(AccountId, Balance, bool)> {
+            self.winner_list.iter().copied().collect()
+        }
+
+        /// Does the contract hold enough to pay out everything it owes?
+        #[ink(message)]
+        pub fn solvency_check(&self) -> bool {
+            self.held_balance() >= self.outstanding_payouts()
+        }
+
+        /// Balance above what the contract tracks as owed, the pot and
+        /// pending payouts. Nonzero means stray funds arrived.
+        #[ink(message)]
+        pub fn balance_mismatch(&self) -> Balance {
+            self.held_balance().saturating_sub(self.outstanding_payouts())
+        }
+
+        /// What the contract holds in the stake currency, the stake token
+        /// for a token raffle and native funds otherwise
+        fn held_balance(&self) -> Balance {
+            match self.stake_token {
+                Some(_) => self.stake_token_balance(),
+                None => self.env().balance(),
+            }
+        }
+
+        /// Stake currency the contract still owes
+        fn outstanding_payouts(&self) -> Balance {
+            let pending: Balance = self.pending_withdrawals.values().sum();
+            if self.finished() {
+                return pending
+            }
+            self.total_balance + pending
+        }
+
+        /// Is Raffle over?
+        #[ink(message)]
+        pub fn finished(&self) -> bool{
+            self.winners >= self.num_winners
+        }
+
+        /// Owner only, start a new round on the same deployment.
+        /// Payouts and bonuses owed from earlier rounds stay claimable.
+        #[ink(message)]
+        pub fn reset(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            if !self.finished() && !self.cancelled {
+                return Err(Error::RaffleInProgress)
+            }
+            while let Some(participant) = self.participant_list.pop() {
+                self.deposits.take(&participant);
+                self.joined_at.take(&participant);
+                self.payers.take(&participant);
+                self.entry_counts.take(&participant);
+            }
+            while self.tickets.pop_drop().is_some() {}
+            while self.winner_list.pop_drop().is_some() {}
+            while self.draw_audit.pop_drop().is_some() {}
+            self.winners = 0;
+            self.total_balance = 0;
+            self.enough_participants = false;
+            self.start_time = 0;
+            self.start_block = 0;
+            self.target_block = 0;
+            self.cancelled = false;
+            self.entries_closed = false;
+            self.finalized_at = 0;
+            self.prizes_swept = false;
+            self.entry_deadline = self.next_entry_deadline();
+            self.round_id = self.round_id.wrapping_add(1);
+            Ok(())
+        }
+
+        /// Owner only, abandon a raffle before the first draw. Every
+        /// deposit can be pulled back by whoever paid it with withdraw().
+        /// Open to anyone once the entry period ended short of the trigger.
+        #[ink(message)]
+        pub fn cancel_and_refund(&mut self) -> Result<()> {
+            if !self.entry_period_ended() {
+                self.ensure_owner()?;
+            }
+            if self.cancelled {
+                return Err(Error::RaffleCancelled)
+            }
+            if self.winners > 0 {
+                return Err(Error::RaffleInProgress)
+            }
+            let participants: Vec<AccountId> = self.participant_list.iter().copied().collect();
+            for participant in participants {
+                let deposit = self.deposit_of(participant);
+                let payer = self.payers.get(&participant).copied().unwrap_or(participant);
+                self.credit(payer, deposit);
+            }
+            self.total_balance = 0;
+            self.cancelled = true;
+            Ok(())
+        }
+
+        /// Owner only, end the entry period now instead of waiting for the
+        /// countdown. A pool still short of the trigger is taken as it is,
+        /// as long as it holds someone for every prize.
+        #[ink(message)]
+        pub fn close_entries(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            if self.cancelled {
+                return Err(Error::RaffleCancelled)
+            }
+            if self.finished() {
+                return Err(Error::RaffleFinished)
+            }
+            // fewer could never finish, and a started draw blocks
+            // cancel_and_refund() and reset()
+            if self.participant_list.len() < self.num_winners {
+                return Err(Error::TooFewParticpants)
+            }
+            if !self.enough_participants {
+                self.enough_participants = true;
+                self.start_time = Self::env().block_timestamp();
+                self.start_block = Self::env().block_number();
+            }
+            self.entries_closed = true;
+            Ok(())
+        }
+
+        /// Has the owner closed entries?
+        #[ink(message)]
+        pub fn entries_closed(&self) -> bool {
+            self.entries_closed
+        }
+
+        /// Timestamp the current round has to reach the trigger by, 0 for none
+        #[ink(message)]
+        pub fn entry_deadline(&self) -> u64 {
+            self.entry_deadline
+        }
+
+        /// Time every round gets to reach the trigger, 0 for no limit.
+        /// The first round's entry_deadline less its creation time.
+        #[ink(message)]
+        pub fn entry_period(&self) -> u64 {
+            self.entry_period_ms
+        }
+
+        /// Deadline for a round starting now, counted from the scheduled
+        /// opening if that is still ahead
+        fn next_entry_deadline(&self) -> u64 {
+            if self.entry_period_ms == 0 {
+                return 0
+            }
+            let start = Self::env().block_timestamp().max(self.scheduled_open_ms);
+            start.saturating_add(self.entry_period_ms)
+        }
+
+        /// Did entry_deadline pass before the trigger was reached?
+        #[ink(message)]
+        pub fn entry_period_ended(&self) -> bool {
+            self.entry_deadline > 0
+                && !self.enough_participants
+                && Self::env().block_timestamp() >= self.entry_deadline
+        }
+
+        /// Was the raffle cancelled?
+        #[ink(message)]
+        pub fn cancelled(&self) -> bool {
+            self.cancelled
+        }
+
+        /// Can deposits be taken back? True once the raffle is cancelled,
+        /// or once the entry period ended short of the trigger and anyone
+        /// may call cancel_and_refund()
+        #[ink(message)]
+        pub fn refunds_available(&self) -> bool {
+            self.cancelled || self.entry_period_ended()
+        }
+
+        /// Pots paid out over all rounds of this deployment
+        #[ink(message)]
+        pub fn lifetime_paid(&self) -> Balance {
+            self.lifetime_paid
+        }
+
+        /// Rounds that drew all their winners
+        #[ink(message)]
+        pub fn rounds_completed(&self) -> u32 {
+            self.rounds_completed
+        }
+
+        /// Is a draw started but not all winners drawn yet?
+        #[ink(message)]
+        pub fn draw_in_progress(&self) -> bool{
+            self.winners > 0 && self.winners < self.num_winners
+        }
+        
+        // Thanks to @LaurentTrk#4763 on discord for get_random_number()
+        // I wouldn't make on time without this
+        // It is up to polkadot-hello-world-jury to decide if my submission is legit
+        fn get_random_number(&self) -> u32 {
+            self.get_random_number_at(0)
+        }
+
+        /// `attempt` gives rejection sampling a fresh subject per redraw
+        fn get_random_number_at(&self, attempt: u32) -> u32 {
+            let random_hash = Randomness::random(&self.random_subject(attempt));
+            Self::as_u32_be(&random_hash.as_ref())
+        }
+
+        /// Same randomness as get_random_number(), 128 bits of it
+        fn get_wide_random_number(&self) -> u128 {
+            let random_hash = Randomness::random(&self.random_subject(0));
+            let mut bytes = [0u8; 16];
+            bytes.copy_from_slice(&random_hash.as_ref()[..16]);
+            u128::from_be_bytes(bytes)
+        }
+
+        fn random_subject(&self, attempt: u32) -> [u8; 44] {
+            let mut subject = [0u8; 44];
+            subject[..40].copy_from_slice(&self.draw_seed());
+            subject[40..].copy_from_slice(&attempt.to_be_bytes());
+            subject
+        }
+
+        /// Subject for the random source, built from the chain and raffle state.
+        /// winners and draw_nonce make each draw differ even when two draws
+        /// land in the same block
+        fn draw_seed(&self) -> [u8; 40] {
+            let mut seed = [0u8; 40];
+            seed[..8].copy_from_slice(&Self::env().block_timestamp().to_be_bytes());
+            seed[8..12].copy_from_slice(&Self::env().block_number().to_be_bytes());
+            seed[12..16].copy_from_slice(&self.winners.to_be_bytes());
+            seed[16..32].copy_from_slice(&self.total_balance.to_be_bytes());
+            seed[32..36].copy_from_slice(&self.draw_nonce.to_be_bytes());
+            seed[36..].copy_from_slice(&self.target_block.to_be_bytes());
+            seed
+        }
+        fn as_u32_be(arr: &[u8]) -> u32 {
+            ((arr[0] as u32) << 24)
+                + ((arr[1] as u32) << 16)
+                + ((arr[2] as u32) << 8)
+                + ((arr[3] as u32) << 0)
+        }
+    }
+
+    /// The ERC20 messages the raffle calls on the stake and bonus tokens.
+    /// Any contract exposing them with the same selectors will do.
+    pub trait Erc20Ref {
+        /// Handle to the token contract at `token`
+        fn at(token: AccountId) -> Self;
+        fn transfer(&self, to: AccountId, value: Balance) -> bool;
+        fn transfer_from(&self, from: AccountId, to: AccountId, value: Balance) -> bool;
+        /// 0 if the call fails
+        fn balance_of(&self, owner: AccountId) -> Balance;
+    }
+
+    /// Calls into a deployed ERC20 contract. Selectors are the first 4 bytes
+    /// of BLAKE2b-256 of the message name.
+    #[cfg(not(test))]
+    pub struct Erc20Token {
+        account: AccountId,
+    }
+
+    #[cfg(not(test))]
+    impl Erc20Ref for Erc20Token {
+        fn at(token: AccountId) -> Self {
+            Self { account: token }
+        }
+
+        fn transfer(&self, to: AccountId, value: Balance) -> bool {
+            use ink_env::call::{build_call, utils::ReturnType, ExecutionInput, Selector};
+            let result = build_call::<ink_env::DefaultEnvironment>()
+                .callee(self.account)
+                .gas_limit(0)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new([0x84, 0xA1, 0x5D, 0xA1]))
+                        .push_arg(to)
+                        .push_arg(value),
+                )
+                .returns::<ReturnType<core::result::Result<(), u8>>>()
+                .fire();
+            matches!(result, Ok(Ok(())))
+        }
+
+        fn transfer_from(&self, from: AccountId, to: AccountId, value: Balance) -> bool {
+            use ink_env::call::{build_call, utils::ReturnType, ExecutionInput, Selector};
+            let result = build_call::<ink_env::DefaultEnvironment>()
+                .callee(self.account)
+                .gas_limit(0)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new([0x0B, 0x39, 0x6F, 0x18]))
+                        .push_arg(from)
+                        .push_arg(to)
+                        .push_arg(value),
+                )
+                .returns::<ReturnType<core::result::Result<(), u8>>>()
+                .fire();
+            matches!(result, Ok(Ok(())))
+        }
+
+        fn balance_of(&self, owner: AccountId) -> Balance {
+            use ink_env::call::{build_call, utils::ReturnType, ExecutionInput, Selector};
+            build_call::<ink_env::DefaultEnvironment>()
+                .callee(self.account)
+                .gas_limit(0)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new([0x0F, 0x75, 0x5A, 0x56]))
+                        .push_arg(owner),
+                )
+                .returns::<ReturnType<Balance>>()
+                .fire()
+                .unwrap_or(0)
+        }
+    }
+
+    #[cfg(not(test))]
+    type StakeToken = Erc20Token;
+    /// The off-chain engine can't call contracts, use the mock token.
+    #[cfg(test)]
+    type StakeToken = tests::MockErc20;
+
+    /// Free balances of arbitrary accounts, for min_account_balance.
+    /// The contracts pallet only reports the contract's own balance, so
+    /// on-chain there is no answer and every entry is turned down until
+    /// the chain offers a lookup, e.g. through a chain extension.
+    #[cfg(feature = "min-account-balance")]
+    pub trait BalanceSource {
+        /// None if the balance of `account` can't be read
+        fn free_balance(account: AccountId) -> Option<Balance>;
+    }
+
+    #[cfg(all(feature = "min-account-balance", not(test)))]
+    pub struct ChainBalances;
+
+    #[cfg(all(feature = "min-account-balance", not(test)))]
+    impl BalanceSource for ChainBalances {
+        fn free_balance(_account: AccountId) -> Option<Balance> {
+            None
+        }
+    }
+
+    #[cfg(all(feature = "min-account-balance", not(test)))]
+    type AccountBalances = ChainBalances;
+    /// Tests set the balances, see tests::set_account_balance.
+    #[cfg(all(feature = "min-account-balance", test))]
+    type AccountBalances = tests::MockBalances;
+
+    /// Where the draw gets its randomness from
+    pub trait RandomSource {
+        /// Random hash for `subject`
+        fn random(subject: &[u8]) -> Hash;
+    }
+
+    /// The chain's randomness, same as env().random()
+    #[cfg(not(test))]
+    pub struct ChainRandom;
+
+    #[cfg(not(test))]
+    impl RandomSource for ChainRandom {
+        fn random(subject: &[u8]) -> Hash {
+            ink_env::random::<ink_env::DefaultEnvironment>(subject)
+                .expect("couldn't decode randomized hash")
+        }
+    }
+
+    #[cfg(not(test))]
+    type Randomness = ChainRandom;
+    /// Tests queue the values the draw sees, see tests::set_random.
+    #[cfg(test)]
+    type Randomness = tests::MockRandom;
+
+    /// Default raffle paying its dust to the zero account, owned by the caller
+    impl Default for Raffle {
+        fn default() -> Self {
+            Self::new(AccountId::from([0x0; 32]))
+        }
+    }
+
+
+    #[cfg(test)]
+    mod tests {
+        use ink_lang as ink;
+        use super::*;
+
+        type Event = <Raffle as ::ink_lang::BaseEvent>::Type;
+        use std::cell::{Cell, RefCell};
+
+        thread_local! {
+            /// Successful (token, to, amount) calls made on the mock ERC20
+            static TOKEN_TRANSFERS: RefCell<Vec<(AccountId, AccountId, Balance)>> = RefCell::new(Vec::new());
+            static TOKEN_TRANSFERS_FAIL: Cell<bool> = Cell::new(false);
+            /// Successful (token, from, to, amount) pulls made on the mock ERC20
+            static TOKEN_PULLS: RefCell<Vec<(AccountId, AccountId, AccountId, Balance)>> = RefCell::new(Vec::new());
+            /// (token, owner, balance) reported by the mock's balance_of
+            static TOKEN_BALANCES: RefCell<Vec<(AccountId, AccountId, Balance)>> = RefCell::new(Vec::new());
+            /// (account, balance) reported by MockBalances
+            static ACCOUNT_BALANCES: RefCell<Vec<(AccountId, Balance)>> = RefCell::new(Vec::new());
+            /// Values MockRandom hands out before falling back to the engine
+            static RANDOM_VALUES: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+            /// Raffle the mock's next transfer calls draw_winner on, like a
+            /// malicious token would
+            static TOKEN_REENTER: Cell<Option<*mut Raffle>> = Cell::new(None);
+            static REENTRY_RESULTS: RefCell<Vec<Result<()>>> = RefCell::new(Vec::new());
+        }
+
+        /// Records calls instead of making them, balances are set by the test
+        pub struct MockErc20 {
+            token: AccountId,
+        }
+
+        impl Erc20Ref for MockErc20 {
+            fn at(token: AccountId) -> Self {
+                Self { token }
+            }
+
+            fn transfer(&self, to: AccountId, value: Balance) -> bool {
+                if TOKEN_TRANSFERS_FAIL.with(|fail| fail.get()) {
+                    return false
+                }
+                if let Some(raffle) = TOKEN_REENTER.with(|reenter| reenter.take()) {
+                    // the raffle is still inside the message that called us
+                    let result = unsafe { (*raffle).draw_winner() };
+                    REENTRY_RESULTS.with(|results| results.borrow_mut().push(result));
+                }
+                TOKEN_TRANSFERS.with(|transfers| transfers.borrow_mut().push((self.token, to, value)));
+                true
+            }
+
+            fn transfer_from(&self, from: AccountId, to: AccountId, value: Balance) -> bool {
+                if TOKEN_TRANSFERS_FAIL.with(|fail| fail.get()) {
+                    return false
+                }
+                TOKEN_PULLS.with(|pulls| pulls.borrow_mut().push((self.token, from, to, value)));
+                true
+            }
+
+            fn balance_of(&self, owner: AccountId) -> Balance {
+                TOKEN_BALANCES.with(|balances| {
+                    balances.borrow().iter()
+                        .filter(|(token, account, _)| *token == self.token && *account == owner)
+                        .map(|(_, _, balance)| *balance)
+                        .sum()
+                })
+            }
+        }
+
+        /// The engine's randomness unless a test queued values with set_random
+        pub struct MockRandom;
+
+        impl RandomSource for MockRandom {
+            fn random(subject: &[u8]) -> Hash {
+                let queued = RANDOM_VALUES.with(|values| {
+                    let mut values = values.borrow_mut();
+                    if values.is_empty() { None } else { Some(values.remove(0)) }
+                });
+                match queued {
+                    Some(value) => {
+                        let mut hash = [0u8; 32];
+                        hash[..4].copy_from_slice(&value.to_be_bytes());
+                        Hash::from(hash)
+                    }
+                    None => ink_env::random::<ink_env::DefaultEnvironment>(subject)
+                        .expect("couldn't decode randomized hash"),
+                }
+            }
+        }
+
+        /// Next values the random source hands out, one per call. A draw
+        /// takes one plus one per rejection sampling redraw, and so does
+        /// next_winner_preview(). A weighted draw sees `value << 96`.
+        fn set_random(values: &[u32]) {
+            RANDOM_VALUES.with(|queue| *queue.borrow_mut() = values.to_vec());
+        }
+
+        /// Only knows the balances a test set
+        #[cfg(feature = "min-account-balance")]
+        pub struct MockBalances;
+
+        #[cfg(feature = "min-account-balance")]
+        impl BalanceSource for MockBalances {
+            fn free_balance(account: AccountId) -> Option<Balance> {
+                ACCOUNT_BALANCES.with(|balances| {
+                    balances.borrow().iter()
+                        .find(|(owner, _)| *owner == account)
+                        .map(|(_, balance)| *balance)
+                })
+            }
+        }
+
+        #[cfg(feature = "min-account-balance")]
+        fn set_account_balance(account: AccountId, balance: Balance) {
+            ACCOUNT_BALANCES.with(|balances| balances.borrow_mut().push((account, balance)));
+        }
+
+        /// We test if the default constructor does its job.
+        #[ink::test]
+        fn default_works() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let raffle = Raffle::new(accounts.alice);
+            assert_eq!(raffle.participants(), 0);
+            assert_eq!(raffle.pot_receiver, accounts.alice);
+        }
+
+        #[ink::test]
+        fn default_impl_works() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let raffle = Raffle::default();
+            assert_eq!(raffle.pot_receiver(), AccountId::from([0x0; 32]));
+            assert_eq!(raffle.owner(), accounts.alice);
+            assert_eq!(raffle.participants(), 0);
+            assert_eq!(raffle.total_balance(), 0);
+            assert!(raffle.get_winners().is_empty());
+            assert_eq!(raffle.status(), RaffleStatus::AwaitingParticipants);
+        }
+
+        #[ink::test]
+        fn test_draw_zero_pot_receiver() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::default();
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            // the final draw would leave the dust on the zero account
+            assert_eq!(raffle.draw_winner(), Err(Error::InvalidConfig));
+            assert_eq!(raffle.winners, 1);
+            assert_eq!(raffle.pending_withdrawals.len(), 0);
+
+            assert_eq!(raffle.set_pot_receiver(accounts.django), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
+        }
+
+        /// We test a simple use case of our contract.
+        #[ink::test]
+        fn test_participate() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            
+            let mut raffle = Raffle::new(accounts.alice);
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.is_participating(accounts.bob), true);
+            
+            // Expect one emitted event:
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 1);
+        }
+        /// A user can send in anywhere between 0.01 and 0.1 tokens.
+        #[ink::test]
+        fn test_deposit_limits() {
+            let accounts =
+              ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+              .expect("Cannot get accounts");
+            
+            let mut raffle = Raffle::new(accounts.alice);
+            
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.participate(accounts.charlie), Err(Error::NoPaymentReceived));
+            assert_eq!(raffle.is_participating(accounts.charlie), false);
+
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN- 1));
+            assert_eq!(raffle.participate(accounts.charlie), Err(Error::EndowmentOutOfLimits));
+            assert_eq!(raffle.is_participating(accounts.charlie), false);
+            
+            do_transfer(accounts.bob, Some(DEPOSIT_MAX+ 1));
+            assert_eq!(raffle.participate(accounts.charlie), Err(Error::EndowmentOutOfLimits));
+            assert_eq!(raffle.is_participating(accounts.charlie), false);
+            
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+            assert_eq!(raffle.is_participating(accounts.charlie), true);
+        }
+
+        /// 15 minute countdown only starts once there are at least 5 players in the pool.
+        #[ink::test]
+        fn test_draw() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            
+            let mut raffle = Raffle::new(accounts.charlie);
+            set_all_participants(&mut raffle);
+
+            // Draw fails since countdown just started
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            let dbg_msg = format!( "start_time {:#?}", raffle.start_time );
+            ink_env::debug_println( &dbg_msg );
+
+            assert_ne!(raffle.start_time, 0);
+
+            // draw 2 winners
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.winners, 1);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.winners, 2);
+
+            // Expect events: 5 NewParticipant events, 1 RaffleTriggered, 1 RaffleOpen, 2 RaffleWinner, 1 RaffleFinished
+            let emitted_events = decoded_events();
+            assert_eq!(emitted_events.len(), 10);
+            assert!(matches!(emitted_events[6], Event::RaffleOpen(_)));
+            let drawn: Vec<(u32, Option<AccountId>)> = winner_events()
+                .into_iter()
+                .map(|RaffleWinner { slot, winner, .. }| (slot, winner))
+                .collect();
+            let winners = raffle.winners_in_draw_order();
+            assert_eq!(drawn, vec![(0, Some(winners[0])), (1, Some(winners[1]))]);
+            assert_ne!(winners[0], winners[1]);
+            assert!(winners.iter().all(|winner| raffle.is_participating(*winner)));
+        }
+
+        /// There are at least 5 players in the pool.
+        #[ink::test]
+        fn test_draw_not_enough_participants() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            
+            let mut raffle = Raffle::new(accounts.charlie);
+            do_transfer(accounts.alice, None);
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+            assert_eq!(raffle.winners, 0);
+
+            // Expect events: 1 NewParticipant event
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 1);
+        }
+
+        /// Drawing before the trigger is a participant problem, not an open countdown.
+        #[ink::test]
+        fn test_draw_not_triggered_emits_no_open() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.draw_winner(), Err(Error::TooFewParticpants));
+
+            let opens = decoded_events()
+                .iter()
+                .filter(|event| matches!(event, Event::RaffleOpen(_)))
+                .count();
+            assert_eq!(opens, 0);
+        }
+        
+
+        /// A user can only play once.
+        #[ink::test]
+        fn test_play_once() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            
+            let mut raffle = Raffle::new(accounts.charlie);
+            do_transfer(accounts.alice, None);
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+            do_transfer(accounts.alice, None);
+            assert_eq!(raffle.participate(accounts.alice), Err(Error::AlreadyParticipating));
+
+        }
+
+        #[ink::test]
+        fn test_participants_in_range() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.alice, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN * 5));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.charlie, Some(DEPOSIT_MAX));
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+
+            assert_eq!(
+                raffle.participants_in_range(DEPOSIT_MIN, DEPOSIT_MIN * 5),
+                [accounts.alice, accounts.bob]
+            );
+            assert_eq!(
+                raffle.participants_in_range(DEPOSIT_MIN * 2, DEPOSIT_MAX),
+                [accounts.bob, accounts.charlie]
+            );
+            assert!(raffle.participants_in_range(0, DEPOSIT_MIN - 1).is_empty());
+        }
+
+        #[ink::test]
+        fn test_draw_in_progress() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.draw_in_progress(), false);
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.draw_in_progress(), false);
+
+            end_countdown(&mut raffle);
+            set_contract_balance(raffle.total_balance());
+
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_in_progress(), true);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_in_progress(), false);
+            assert_eq!(raffle.finished(), true);
+        }
+
+        #[cfg(feature = "min-account-balance")]
+        #[ink::test]
+        fn test_min_account_balance() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_min_account_balance(accounts.django, 1_000);
+            set_account_balance(accounts.bob, 1_000);
+            set_account_balance(accounts.charlie, 999);
+
+            // Charlie holds too little, Bob pays for him
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.charlie), Err(Error::AccountBalanceTooLow));
+            assert_eq!(raffle.is_participating(accounts.charlie), false);
+            // nobody knows Eve's balance
+            assert_eq!(raffle.participate(accounts.eve), Err(Error::AccountBalanceTooLow));
+
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+        }
+
+        #[cfg(not(feature = "min-account-balance"))]
+        #[ink::test]
+        #[should_panic]
+        fn test_min_account_balance_needs_feature() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            Raffle::new_with_min_account_balance(accounts.django, 1_000);
+        }
+
+        #[ink::test]
+        fn test_remaining_pot_capacity() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.remaining_pot_capacity(), Balance::MAX);
+            do_transfer(accounts.alice, None);
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+            assert_eq!(raffle.remaining_pot_capacity(), Balance::MAX);
+
+            let mut raffle = Raffle::new_with_max_pot(accounts.django, DEPOSIT_MIN * 3);
+            assert_eq!(raffle.remaining_pot_capacity(), DEPOSIT_MIN * 3);
+            do_transfer(accounts.alice, Some(DEPOSIT_MIN * 2));
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+            assert_eq!(raffle.remaining_pot_capacity(), DEPOSIT_MIN);
+
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN * 2));
+            assert_eq!(raffle.participate(accounts.bob), Err(Error::PotCapReached));
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.remaining_pot_capacity(), 0);
+        }
+
+        #[ink::test]
+        fn test_winners_in_draw_order() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            assert!(raffle.winners_in_draw_order().is_empty());
+
+            end_countdown(&mut raffle);
+            set_contract_balance(raffle.total_balance());
+
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let first = raffle.winner_address()[0].expect("first winner drawn");
+            assert_eq!(raffle.winners_in_draw_order(), [first]);
+
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let second = raffle.winner_address()[1].expect("second winner drawn");
+            assert_eq!(raffle.winners_in_draw_order(), [first, second]);
+        }
+
+        #[ink::test]
+        fn test_max_batch_size() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.max_batch_size(), MAX_BATCH_SIZE);
+            assert!(raffle.max_batch_size() > 0);
+        }
+
+        const BONUS: Balance = 1_000;
+
+        fn draw_bonus_raffle(pot_receiver: AccountId) -> Raffle {
+            let mut raffle = Raffle::new_with_bonus(pot_receiver, AccountId::from([0x10; 32]), BONUS);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            set_contract_balance(raffle.total_balance());
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            raffle
+        }
+
+        #[ink::test]
+        fn test_bonus_prize() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let raffle = draw_bonus_raffle(accounts.django);
+
+            // native pot is ready for the winners
+            let share = raffle.total_balance() / RAFFLE_WINNERS as Balance;
+            for winner in raffle.winners_in_draw_order() {
+                assert_eq!(raffle.pending_withdrawal(winner), share);
+            }
+
+            // each winner got the bonus token
+            let token = AccountId::from([0x10; 32]);
+            let expected: Vec<_> = raffle
+                .winners_in_draw_order()
+                .into_iter()
+                .map(|winner| (token, winner, BONUS))
+                .collect();
+            TOKEN_TRANSFERS.with(|transfers| assert_eq!(*transfers.borrow(), expected));
+        }
+
+        #[ink::test]
+        fn test_bonus_prize_failed_transfer() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            TOKEN_TRANSFERS_FAIL.with(|fail| fail.set(true));
+            let mut raffle = draw_bonus_raffle(accounts.django);
+            let winners = raffle.winners_in_draw_order();
+            for winner in winners.iter() {
+                let slots = winners.iter().filter(|w| *w == winner).count() as Balance;
+                assert_eq!(raffle.pending_bonus(*winner), BONUS * slots);
+            }
+
+            // the token works again, winner pulls the bonus
+            TOKEN_TRANSFERS_FAIL.with(|fail| fail.set(false));
+            let winner = winners[0];
+            let owed = raffle.pending_bonus(winner);
+            do_transfer(winner, Some(0));
+            assert_eq!(raffle.claim_bonus(), Ok(()));
+            assert_eq!(raffle.pending_bonus(winner), 0);
+            assert_eq!(raffle.claim_bonus(), Err(Error::NothingToWithdraw));
+            TOKEN_TRANSFERS.with(|transfers| {
+                assert_eq!(*transfers.borrow(), [(AccountId::from([0x10; 32]), winner, owed)])
+            });
+        }
+
+        #[ink::test]
+        fn test_transfer_ownership() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.transfer_ownership(accounts.bob), Err(Error::NotOwner));
+
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(raffle.pending_owner(), Some(accounts.bob));
+            // alice stays in charge until bob accepts
+            assert_eq!(raffle.owner(), accounts.alice);
+            assert_eq!(raffle.pause(), Ok(()));
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.accept_ownership(), Ok(()));
+            assert_eq!(raffle.owner(), accounts.bob);
+            assert_eq!(raffle.pending_owner(), None);
+            assert_eq!(raffle.unpause(), Ok(()));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.pause(), Err(Error::NotOwner));
+
+            let transfers: Vec<(Option<AccountId>, Option<AccountId>)> = decoded_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::OwnershipTransferred(OwnershipTransferred { previous, new, .. }) => Some((previous, new)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(transfers, vec![(Some(accounts.alice), Some(accounts.bob))]);
+        }
+
+        #[ink::test]
+        fn test_accept_ownership_not_pending() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.accept_ownership(), Err(Error::NotPendingOwner));
+            assert_eq!(raffle.transfer_ownership(accounts.bob), Ok(()));
+
+            do_transfer(accounts.charlie, Some(0));
+            assert_eq!(raffle.accept_ownership(), Err(Error::NotPendingOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.accept_ownership(), Err(Error::NotPendingOwner));
+            assert_eq!(raffle.owner(), accounts.alice);
+            assert_eq!(raffle.pending_owner(), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn test_solvency_check() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+
+            set_contract_balance(raffle.total_balance());
+            assert_eq!(raffle.solvency_check(), true);
+
+            // funds went missing
+            set_contract_balance(raffle.total_balance() - 1);
+            assert_eq!(raffle.solvency_check(), false);
+        }
+
+        #[ink::test]
+        fn test_solvency_check_token() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let token = AccountId::from(STAKE_TOKEN);
+            let contract = AccountId::from([0x07; 32]);
+
+            let mut raffle = Raffle::new_with_stake_token(accounts.django, token);
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.participate_with_token(accounts.bob, DEPOSIT_MAX), Ok(()));
+
+            // native funds don't cover a token pot
+            set_contract_balance(DEPOSIT_MAX);
+            assert_eq!(raffle.solvency_check(), false);
+            assert_eq!(raffle.balance_mismatch(), 0);
+
+            TOKEN_BALANCES.with(|balances| balances.borrow_mut().push((token, contract, DEPOSIT_MAX + 7)));
+            set_contract_balance(0);
+            assert_eq!(raffle.solvency_check(), true);
+            assert_eq!(raffle.balance_mismatch(), 7);
+        }
+
+        #[ink::test]
+        fn test_balance_mismatch() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            set_contract_balance(raffle.total_balance());
+            assert_eq!(raffle.balance_mismatch(), 0);
+
+            // someone sent funds straight to the contract
+            set_contract_balance(raffle.total_balance() + 7);
+            assert_eq!(raffle.balance_mismatch(), 7);
+
+            // a shortfall is solvency_check's business
+            set_contract_balance(raffle.total_balance() - 1);
+            assert_eq!(raffle.balance_mismatch(), 0);
+        }
+
+        #[ink::test]
+        fn test_draw_candidates() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert!(raffle.draw_candidates().is_empty());
+            set_all_participants(&mut raffle);
+
+            let candidates = raffle.draw_candidates();
+            assert_eq!(candidates.len() as u32, raffle.participants());
+            assert_eq!(
+                candidates,
+                [accounts.alice, accounts.bob, accounts.charlie, accounts.eve, accounts.frank]
+            );
+        }
+
+        #[ink::test]
+        fn test_withdraw() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+
+            // draws only record the winners, even if the contract couldn't pay
+            set_contract_balance(0);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
+            let winner = raffle.winners_in_draw_order()[0];
+            let share = raffle.pending_withdrawal(winner);
+            assert!(share > 0);
+
+            // a failed transfer keeps the payout
+            do_transfer(winner, Some(0));
+            assert_eq!(raffle.withdraw(), Err(Error::TransferError));
+            assert_eq!(raffle.pending_withdrawal(winner), share);
+
+            set_contract_balance(raffle.total_balance());
+            let before = ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(winner)
+                .expect("Cannot get account balance");
+            assert_eq!(raffle.withdraw(), Ok(()));
+            assert_eq!(raffle.pending_withdrawal(winner), 0);
+            let after = ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(winner)
+                .expect("Cannot get account balance");
+            assert_eq!(after - before, share);
+            assert_eq!(raffle.withdraw(), Err(Error::NothingToWithdraw));
+        }
+
+        #[ink::test]
+        fn test_pot_split_between_winners() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            set_contract_balance(raffle.total_balance());
+
+            let pot = raffle.total_balance();
+            let share = pot / 2;
+            let winners = raffle.winners_in_draw_order();
+            assert_eq!(winners.len(), 2);
+            for winner in winners {
+                let before = ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(winner)
+                    .expect("Cannot get account balance");
+                do_transfer(winner, Some(0));
+                assert_eq!(raffle.withdraw(), Ok(()));
+                let after = ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(winner)
+                    .expect("Cannot get account balance");
+                assert_eq!(after - before, share);
+            }
+            // the dust is left for pot_receiver
+            assert_eq!(raffle.pending_withdrawal(accounts.django), pot - share * 2);
+        }
+
+        const HOUSE: [u8; 32] = [0x20; 32];
+
+        #[ink::test]
+        fn test_raffle_triggered_event() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+
+            // fired once, right after the last NewParticipant
+            let emitted_events = decoded_events();
+            assert_eq!(emitted_events.len(), RAFFLE_TRIGGER as usize + 1);
+            assert!(matches!(emitted_events.last(), Some(Event::RaffleTriggered(_))));
+            let triggered: Vec<(u64, Option<u64>)> = emitted_events
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::RaffleTriggered(RaffleTriggered { start_time, deadline, .. }) => Some((start_time, deadline)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(triggered, vec![(raffle.start_time, Some(raffle.start_time + DURATION_IN_MS))]);
+        }
+
+        #[ink::test]
+        fn test_leave() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            for participant in [accounts.alice, accounts.bob, accounts.charlie].iter() {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+
+            do_transfer(accounts.eve, Some(0));
+            assert_eq!(raffle.leave(), Err(Error::NotParticipating));
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.leave(), Ok(()));
+            assert_eq!(raffle.get_participants(), [accounts.alice, accounts.charlie]);
+            assert_eq!(raffle.total_balance(), DEPOSIT_MIN * 2);
+            assert_eq!(raffle.deposit_of(accounts.bob), 0);
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), DEPOSIT_MIN);
+            assert_eq!(raffle.leave(), Err(Error::NotParticipating));
+
+            set_contract_balance(DEPOSIT_MIN * 3);
+            assert_eq!(raffle.withdraw(), Ok(()));
+
+            // bob can come back
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.get_participants(), [accounts.alice, accounts.charlie, accounts.bob]);
+        }
+
+        #[ink::test]
+        fn test_refund_goes_to_payer() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            // bob pays for charlie and eve
+            let mut raffle = Raffle::new(accounts.django);
+            for participant in [accounts.charlie, accounts.eve].iter() {
+                do_transfer(accounts.bob, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+
+            do_transfer(accounts.charlie, Some(0));
+            assert_eq!(raffle.leave(), Ok(()));
+            assert_eq!(raffle.pending_withdrawal(accounts.charlie), 0);
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), DEPOSIT_MIN);
+
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.cancel_and_refund(), Ok(()));
+            assert_eq!(raffle.pending_withdrawal(accounts.eve), 0);
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), DEPOSIT_MIN * 2);
+
+            set_contract_balance(DEPOSIT_MIN * 2);
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.withdraw(), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_start_time_set_once() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_config(accounts.django, 2, RAFFLE_TRIGGER, 1_000, 0);
+            set_all_participants(&mut raffle);
+            let start_time = raffle.start_time;
+
+            // bob can't leave and rejoin to restart the countdown
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.leave(), Err(Error::CountdownStarted));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Err(Error::AlreadyParticipating));
+
+            // entries past the trigger don't move it either
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            do_transfer(accounts.django, None);
+            assert_eq!(raffle.participate(accounts.django), Ok(()));
+            assert_eq!(raffle.participants(), RAFFLE_TRIGGER + 1);
+            assert_eq!(raffle.start_time, start_time);
+        }
+
+        #[ink::test]
+        fn test_position_of() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            let joined = [accounts.charlie, accounts.alice, accounts.eve];
+            for participant in joined.iter() {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            for (index, participant) in joined.iter().enumerate() {
+                assert_eq!(raffle.position_of(*participant), Some(index as u32));
+            }
+            assert_eq!(raffle.position_of(accounts.bob), None);
+
+            // leaving shifts the later entrants down
+            do_transfer(accounts.charlie, Some(0));
+            assert_eq!(raffle.leave(), Ok(()));
+            assert_eq!(raffle.position_of(accounts.charlie), None);
+            assert_eq!(raffle.position_of(accounts.eve), Some(1));
+        }
+
+        #[ink::test]
+        fn test_entry_limit() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_entry_limit(accounts.django, 2);
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.bob, Some(DEPOSIT_MAX));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Err(Error::EntryLimitReached));
+
+            // two tickets in the pool, one deposit for both
+            assert_eq!(raffle.entries_of(accounts.bob), 2);
+            assert_eq!(raffle.participants(), 1);
+            assert_eq!(raffle.get_participants(), [accounts.bob]);
+            assert_eq!(raffle.draw_candidates(), [accounts.bob, accounts.bob]);
+            assert_eq!(raffle.deposit_of(accounts.bob), DEPOSIT_MIN + DEPOSIT_MAX);
+            assert_eq!(raffle.total_balance(), DEPOSIT_MIN + DEPOSIT_MAX);
+
+            // leaving gives up every ticket
+            do_transfer(accounts.charlie, None);
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.leave(), Ok(()));
+            assert_eq!(raffle.get_participants(), [accounts.charlie]);
+            assert_eq!(raffle.draw_candidates(), [accounts.charlie]);
+            assert_eq!(raffle.entries_of(accounts.bob), 0);
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), DEPOSIT_MIN + DEPOSIT_MAX);
+        }
+
+        #[ink::test]
+        fn test_entry_limit_two_payers() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_entry_limit(accounts.django, 3);
+            do_transfer(accounts.frank, Some(DEPOSIT_MAX));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            // bob's next ticket from another wallet would be refunded to frank
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Err(Error::PayerMismatch));
+            assert_eq!(raffle.entries_of(accounts.bob), 1);
+            do_transfer(accounts.frank, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.leave(), Ok(()));
+            assert_eq!(raffle.pending_withdrawal(accounts.frank), DEPOSIT_MAX + DEPOSIT_MIN);
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), 0);
+
+            // after leaving anyone may pay for a fresh entry
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_entry_limit_draw() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            // five tickets but only two accounts, nobody takes both prizes
+            let mut raffle = Raffle::new_with_entry_limit(accounts.django, 3);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            for participant in &[accounts.bob, accounts.bob, accounts.bob, accounts.charlie, accounts.charlie] {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            // the trigger counts accounts, not tickets
+            assert_eq!(raffle.status(), RaffleStatus::AwaitingParticipants);
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.close_entries(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let mut winners = raffle.winners_in_draw_order();
+            winners.sort();
+            let mut expected = vec![accounts.bob, accounts.charlie];
+            expected.sort();
+            assert_eq!(winners, expected);
+        }
+
+        #[ink::test]
+        fn test_tickets_draw_pool() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_entry_limit(accounts.django, 2);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            let entries = [accounts.bob, accounts.charlie, accounts.bob, accounts.eve, accounts.frank, accounts.django];
+            for participant in entries.iter() {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            assert_eq!(raffle.participants(), 5);
+            assert_eq!(raffle.draw_candidates(), entries);
+            do_transfer(accounts.alice, Some(0));
+            end_countdown(&mut raffle);
+
+            // bob's second ticket is a chance of its own
+            let index = raffle.next_non_winner(raffle.get_random_index(2).0);
+            assert_eq!(index, 2);
+            assert_eq!(raffle.tickets.get(index), Some(&accounts.bob));
+
+            // the audit records the ticket pool the draw picked from
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let (winner, index, _, pool_size, _) = raffle.winner_audit(0).expect("slot 0 drawn");
+            assert_eq!(pool_size, 6);
+            assert_eq!(entries[index as usize], winner);
+        }
+
+        #[ink::test]
+        #[should_panic]
+        fn test_entry_limit_zero() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            Raffle::new_with_entry_limit(accounts.django, 0);
+        }
+
+        #[ink::test]
+        fn test_leave_after_trigger() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.leave(), Err(Error::CountdownStarted));
+            assert_eq!(raffle.participants(), RAFFLE_TRIGGER);
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), 0);
+        }
+
+        const STAKE_TOKEN: [u8; 32] = [0x30; 32];
+
+        #[ink::test]
+        fn test_participate_with_token() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let token = AccountId::from(STAKE_TOKEN);
+            let contract = AccountId::from([0x07; 32]);
+
+            let mut raffle = Raffle::new_with_stake_token(accounts.django, token);
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Err(Error::TokenStakeOnly));
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(
+                raffle.participate_with_token(accounts.bob, DEPOSIT_MAX + 1),
+                Err(Error::EndowmentOutOfLimits)
+            );
+            assert_eq!(raffle.participate_with_token(accounts.bob, DEPOSIT_MAX), Ok(()));
+            assert_eq!(raffle.deposit_of(accounts.bob), DEPOSIT_MAX);
+            assert_eq!(raffle.total_balance(), DEPOSIT_MAX);
+            assert_eq!(
+                raffle.participate_with_token(accounts.bob, DEPOSIT_MIN),
+                Err(Error::AlreadyParticipating)
+            );
+
+            // bob pays charlie's stake, a failed pull leaves no entry
+            TOKEN_TRANSFERS_FAIL.with(|fail| fail.set(true));
+            assert_eq!(raffle.participate_with_token(accounts.charlie, DEPOSIT_MIN), Err(Error::TransferError));
+            assert_eq!(raffle.participants(), 1);
+            TOKEN_TRANSFERS_FAIL.with(|fail| fail.set(false));
+            assert_eq!(raffle.participate_with_token(accounts.charlie, DEPOSIT_MIN), Ok(()));
+
+            TOKEN_PULLS.with(|pulls| {
+                assert_eq!(
+                    *pulls.borrow(),
+                    [(token, accounts.bob, contract, DEPOSIT_MAX), (token, accounts.bob, contract, DEPOSIT_MIN)]
+                )
+            });
+
+            // refunds go back in the token, to bob who paid
+            do_transfer(accounts.charlie, Some(0));
+            assert_eq!(raffle.leave(), Ok(()));
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.withdraw(), Ok(()));
+            TOKEN_TRANSFERS.with(|transfers| {
+                assert_eq!(*transfers.borrow(), [(token, accounts.bob, DEPOSIT_MIN)])
+            });
+        }
+
+        #[ink::test]
+        fn test_stake_token_balance() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            let token = AccountId::from(STAKE_TOKEN);
+            let contract = AccountId::from([0x07; 32]);
+
+            assert_eq!(Raffle::new(accounts.django).stake_token_balance(), 0);
+
+            let raffle = Raffle::new_with_stake_token(accounts.django, token);
+            assert_eq!(raffle.stake_token_balance(), 0);
+            TOKEN_BALANCES.with(|balances| {
+                let mut balances = balances.borrow_mut();
+                balances.push((token, contract, DEPOSIT_MAX));
+                // other holders and other tokens don't count
+                balances.push((token, accounts.bob, DEPOSIT_MIN));
+                balances.push((AccountId::from([0x01; 32]), contract, DEPOSIT_MIN));
+            });
+            assert_eq!(raffle.stake_token_balance(), DEPOSIT_MAX);
+            assert_eq!(MockErc20::at(token).balance_of(accounts.bob), DEPOSIT_MIN);
+        }
+
+        #[ink::test]
+        fn test_participate_with_token_native_raffle() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(
+                raffle.participate_with_token(accounts.bob, DEPOSIT_MIN),
+                Err(Error::NativeStakeOnly)
+            );
+        }
+
+        #[ink::test]
+        fn test_set_pot_receiver() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.pot_receiver(), accounts.django);
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.set_pot_receiver(accounts.bob), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.set_pot_receiver(accounts.eve), Ok(()));
+            assert_eq!(raffle.pot_receiver(), accounts.eve);
+
+            let changes: Vec<(Option<AccountId>, Option<AccountId>)> = decoded_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::PotReceiverChanged(PotReceiverChanged { old, new, .. }) => Some((old, new)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(changes, vec![(Some(accounts.django), Some(accounts.eve))]);
+
+            // too late once the pot is credited
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.set_pot_receiver(accounts.charlie), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.set_pot_receiver(accounts.django), Err(Error::RaffleFinished));
+            assert_eq!(raffle.pot_receiver(), accounts.charlie);
+        }
+
+        #[ink::test]
+        fn test_allowlist() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_allowlist(accounts.django, true);
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.allow(accounts.bob), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.allow(accounts.bob), Ok(()));
+            assert_eq!(raffle.allow(accounts.charlie), Ok(()));
+            assert_eq!(raffle.disallow(accounts.charlie), Ok(()));
+
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.charlie, None);
+            assert_eq!(raffle.participate(accounts.charlie), Err(Error::NotAllowed));
+            do_transfer(accounts.eve, None);
+            assert_eq!(raffle.participate(accounts.eve), Err(Error::NotAllowed));
+            assert_eq!(raffle.participants(), 1);
+
+            // without the flag the list is ignored
+            let mut raffle = Raffle::new_with_allowlist(accounts.django, false);
+            assert_eq!(raffle.is_allowed(accounts.eve), true);
+            assert_eq!(raffle.participate(accounts.eve), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_can_participate() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.can_participate(accounts.bob), true);
+
+            // already in
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.can_participate(accounts.bob), false);
+
+            // paused
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.pause(), Ok(()));
+            assert_eq!(raffle.can_participate(accounts.charlie), false);
+            assert_eq!(raffle.unpause(), Ok(()));
+            assert_eq!(raffle.can_participate(accounts.charlie), true);
+
+            // finished
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.can_participate(accounts.django), false);
+        }
+
+        #[ink::test]
+        fn test_can_participate_pool_full_and_allowlist() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_config(accounts.django, 1, 1, DURATION_IN_MS, 1);
+            assert_eq!(raffle.can_participate(accounts.charlie), true);
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.can_participate(accounts.charlie), false);
+
+            do_transfer(accounts.alice, Some(0));
+            let mut raffle = Raffle::new_with_allowlist(accounts.django, true);
+            assert_eq!(raffle.can_participate(accounts.charlie), false);
+            assert_eq!(raffle.allow(accounts.charlie), Ok(()));
+            assert_eq!(raffle.can_participate(accounts.charlie), true);
+        }
+
+        #[ink::test]
+        fn test_receiver_entry() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_receiver_entry(accounts.django, false);
+            do_transfer(accounts.django, None);
+            assert_eq!(raffle.participate(accounts.django), Err(Error::ReceiverCannotParticipate));
+            // nor on someone else's dime
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.django), Err(Error::ReceiverCannotParticipate));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+
+            let mut raffle = Raffle::new_with_receiver_entry(accounts.django, true);
+            do_transfer(accounts.django, None);
+            assert_eq!(raffle.participate(accounts.django), Ok(()));
+
+            // default keeps the old behaviour
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.participate(accounts.django), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_batch_participate() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            let participants = vec![accounts.alice, accounts.bob, accounts.charlie, accounts.eve];
+            let amounts = vec![DEPOSIT_MIN, DEPOSIT_MAX, DEPOSIT_MIN, DEPOSIT_MIN * 2];
+            let sum: Balance = amounts.iter().sum();
+
+            // mismatched lists, too little paid
+            do_transfer(accounts.frank, Some(sum));
+            assert_eq!(
+                raffle.batch_participate(participants.clone(), amounts[..3].to_vec()),
+                Err(Error::InvalidBatch)
+            );
+            do_transfer(accounts.frank, Some(sum - 1));
+            assert_eq!(
+                raffle.batch_participate(participants.clone(), amounts.clone()),
+                Err(Error::EndowmentOutOfLimits)
+            );
+
+            do_transfer(accounts.frank, Some(sum + 1));
+            assert_eq!(raffle.batch_participate(participants.clone(), amounts.clone()), Ok(()));
+            assert_eq!(raffle.get_participants(), participants);
+            assert_eq!(raffle.deposit_of(accounts.bob), DEPOSIT_MAX);
+            assert_eq!(raffle.total_balance(), sum);
+            // the overpaid unit waits for frank
+            assert_eq!(raffle.pending_withdrawal(accounts.frank), 1);
+        }
+
+        #[ink::test]
+        fn test_refund_overpay() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.bob, Some(DEPOSIT_MAX + 7));
+            assert_eq!(raffle.participate(accounts.bob), Err(Error::EndowmentOutOfLimits));
+
+            let mut raffle = Raffle::new_with_refund_overpay(accounts.django, true);
+            do_transfer(accounts.frank, Some(DEPOSIT_MAX + 7));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.deposit_of(accounts.bob), DEPOSIT_MAX);
+            assert_eq!(raffle.total_balance(), DEPOSIT_MAX);
+            // the excess waits for whoever paid
+            assert_eq!(raffle.pending_withdrawal(accounts.frank), 7);
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn test_batch_participate_too_large() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            let size = raffle.max_batch_size() as usize + 1;
+            let participants: Vec<AccountId> = (0..size).map(|i| AccountId::from([0x40 + i as u8; 32])).collect();
+            do_transfer(accounts.frank, Some(DEPOSIT_MIN * size as Balance));
+            assert_eq!(
+                raffle.batch_participate(participants.clone(), vec![DEPOSIT_MIN; size]),
+                Err(Error::InvalidBatch)
+            );
+            assert_eq!(raffle.participants(), 0);
+
+            // one less fits
+            do_transfer(accounts.frank, Some(DEPOSIT_MIN * (size - 1) as Balance));
+            assert_eq!(
+                raffle.batch_participate(participants[1..].to_vec(), vec![DEPOSIT_MIN; size - 1]),
+                Ok(())
+            );
+            assert_eq!(raffle.participants(), raffle.max_batch_size());
+        }
+
+        #[ink::test]
+        fn test_batch_participate_entry_limit() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_entry_limit(accounts.django, 2);
+            do_transfer(accounts.frank, Some(DEPOSIT_MIN * 3));
+            assert_eq!(
+                raffle.batch_participate(
+                    vec![accounts.alice, accounts.bob, accounts.alice],
+                    vec![DEPOSIT_MIN; 3],
+                ),
+                Ok(())
+            );
+            assert_eq!(raffle.participants(), 2);
+            assert_eq!(raffle.entries_of(accounts.alice), 2);
+            assert_eq!(raffle.deposit_of(accounts.alice), DEPOSIT_MIN * 2);
+
+            // bob holds one, two more go over the cap like participate would
+            do_transfer(accounts.frank, Some(DEPOSIT_MIN * 2));
+            assert_eq!(
+                raffle.batch_participate(vec![accounts.bob, accounts.bob], vec![DEPOSIT_MIN; 2]),
+                Err(Error::EntryLimitReached)
+            );
+            assert_eq!(raffle.entries_of(accounts.bob), 1);
+        }
+
+        #[ink::test]
+        fn test_batch_participate_duplicate() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.frank, Some(DEPOSIT_MIN * 3));
+            assert_eq!(
+                raffle.batch_participate(
+                    vec![accounts.alice, accounts.bob, accounts.alice],
+                    vec![DEPOSIT_MIN; 3],
+                ),
+                Err(Error::AlreadyParticipating)
+            );
+            assert_eq!(raffle.participants(), 0);
+            assert_eq!(raffle.total_balance(), 0);
+
+            // nor someone who already entered
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.frank, Some(DEPOSIT_MIN * 2));
+            assert_eq!(
+                raffle.batch_participate(vec![accounts.alice, accounts.bob], vec![DEPOSIT_MIN; 2]),
+                Err(Error::AlreadyParticipating)
+            );
+            assert_eq!(raffle.participants(), 1);
+        }
+
+        #[ink::test]
+        fn test_winner_prizes() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_fee(accounts.django, AccountId::from(HOUSE), MAX_FEE_BPS);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let first = raffle.winners_in_draw_order()[0];
+            assert_eq!(raffle.winner_prizes(), [(first, 0)]);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            let prizes = raffle.winner_prizes();
+            assert_eq!(prizes.len(), 2);
+            assert_eq!(prizes[0].0, first);
+            for (winner, prize) in prizes.iter() {
+                assert_eq!(raffle.pending_withdrawal(*winner), *prize);
+            }
+            // whatever the winners didn't get went to the house and pot_receiver
+            let distributed: Balance = prizes.iter().map(|(_, prize)| prize).sum();
+            let rest = raffle.pending_withdrawal(AccountId::from(HOUSE))
+                + raffle.pending_withdrawal(accounts.django);
+            assert_eq!(distributed + rest, raffle.total_balance());
+        }
+
+        #[ink::test]
+        fn test_winner_claimed() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_all(), Ok(()));
+            set_contract_balance(raffle.total_balance());
+
+            let records = raffle.winner_records();
+            assert_eq!(records.len(), 2);
+            assert!(records.iter().all(|(_, prize, claimed)| *prize > 0 && !claimed));
+
+            let (first, prize, _) = records[0];
+            do_transfer(first, Some(0));
+            assert_eq!(raffle.withdraw(), Ok(()));
+            let records = raffle.winner_records();
+            assert_eq!(records[0], (first, prize, true));
+            assert_eq!(records[1].2, false);
+        }
+
+        #[ink::test]
+        fn test_pot_transferred_event() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let winner = raffle.winners_in_draw_order()[0];
+            let share = raffle.pending_withdrawal(winner);
+
+            // nothing recorded while the transfer fails
+            let events_before = decoded_events().len();
+            set_contract_balance(0);
+            do_transfer(winner, Some(0));
+            assert_eq!(raffle.withdraw(), Err(Error::TransferError));
+            assert_eq!(decoded_events().len(), events_before);
+
+            set_contract_balance(raffle.total_balance());
+            assert_eq!(raffle.withdraw(), Ok(()));
+            let emitted_events = decoded_events();
+            assert_eq!(emitted_events.len(), events_before + 1);
+            let transferred: Vec<(Option<AccountId>, Balance)> = emitted_events
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::PotTransferred(PotTransferred { to, amount, .. }) => Some((to, amount)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(transferred, vec![(Some(winner), share)]);
+        }
+
+        #[ink::test]
+        fn test_status() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.status(), RaffleStatus::AwaitingParticipants);
+
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.status(), RaffleStatus::CountdownActive);
+
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.status(), RaffleStatus::ReadyToDraw);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.status(), RaffleStatus::ReadyToDraw);
+
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.status(), RaffleStatus::Completed);
+
+            assert_eq!(raffle.reset(), Ok(()));
+            assert_eq!(raffle.status(), RaffleStatus::AwaitingParticipants);
+            assert_eq!(raffle.cancel_and_refund(), Ok(()));
+            assert_eq!(raffle.status(), RaffleStatus::Completed);
+        }
+
+        #[ink::test]
+        fn test_draw_owner_only() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.owner(), accounts.alice);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+
+            // a participant can't trigger the draw
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.draw_winner(), Err(Error::NotOwner));
+            assert_eq!(raffle.winners, 0);
+
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.winners, 1);
+        }
+
+        #[ink::test]
+        fn test_pause() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.pause(), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.pause(), Ok(()));
+            assert_eq!(raffle.paused(), true);
+
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Err(Error::Paused));
+            assert_eq!(raffle.participants(), 0);
+
+            assert_eq!(raffle.unpause(), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.unpause(), Ok(()));
+            assert_eq!(raffle.paused(), false);
+
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.participants(), 1);
+        }
+
+        #[ink::test]
+        fn test_draw_while_paused() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.pause(), Ok(()));
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
+        }
+
+        #[ink::test]
+        fn test_entry_deadline() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let deadline = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block timestamp") + 10;
+            let mut raffle = Raffle::new_with_entry_deadline(accounts.django, deadline);
+            assert_eq!(raffle.entry_deadline(), deadline);
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            set_contract_balance(raffle.total_balance());
+
+            do_transfer(accounts.charlie, Some(0));
+            assert_eq!(raffle.cancel_and_refund(), Err(Error::NotOwner));
+            while !raffle.entry_period_ended() {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+
+            do_transfer(accounts.charlie, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.charlie), Err(Error::EntryPeriodEnded));
+            do_transfer(accounts.charlie, Some(0));
+            assert_eq!(raffle.cancel_and_refund(), Ok(()));
+            assert_eq!(raffle.cancelled(), true);
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), DEPOSIT_MIN);
+        }
+
+        #[ink::test]
+        fn test_entry_deadline_every_round() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let deadline = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block timestamp") + 10;
+            let mut raffle = Raffle::new_with_entry_deadline(accounts.django, deadline);
+            assert_eq!(raffle.entry_period(), 10);
+            while !raffle.entry_period_ended() {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+            assert_eq!(raffle.cancel_and_refund(), Ok(()));
+            assert_eq!(raffle.reset(), Ok(()));
+
+            // the next round gets a fresh period of its own
+            let now = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block timestamp");
+            assert_eq!(raffle.entry_deadline(), now + 10);
+            assert_eq!(raffle.entry_period_ended(), false);
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            set_contract_balance(raffle.total_balance());
+            while !raffle.entry_period_ended() {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+
+            // and expires just the same, anyone may refund it
+            do_transfer(accounts.charlie, Some(0));
+            assert_eq!(raffle.cancel_and_refund(), Ok(()));
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), DEPOSIT_MIN);
+        }
+
+        #[ink::test]
+        fn test_refunds_available() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let deadline = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block timestamp") + 10;
+            let mut raffle = Raffle::new_with_entry_deadline(accounts.django, deadline);
+            assert_eq!(raffle.refunds_available(), false);
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            set_contract_balance(raffle.total_balance());
+            assert_eq!(raffle.refunds_available(), false);
+
+            while !raffle.entry_period_ended() {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+            assert_eq!(raffle.refunds_available(), true);
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.cancel_and_refund(), Ok(()));
+            assert_eq!(raffle.refunds_available(), true);
+        }
+
+        #[ink::test]
+        fn test_refunds_available_after_trigger() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let deadline = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block timestamp") + 100;
+            let mut raffle = Raffle::new_with_entry_deadline(accounts.django, deadline);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            while ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block timestamp") < deadline
+            {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+            // the pool filled in time, the deadline no longer matters
+            assert_eq!(raffle.refunds_available(), false);
+            assert_eq!(raffle.cancel_and_refund(), Ok(()));
+            assert_eq!(raffle.refunds_available(), true);
+        }
+
+        #[ink::test]
+        fn test_cancel_and_refund() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            let joined = [
+                (accounts.bob, DEPOSIT_MIN),
+                (accounts.charlie, DEPOSIT_MAX),
+                (accounts.eve, DEPOSIT_MIN * 2),
+            ];
+            for (participant, deposit) in joined.iter() {
+                do_transfer(*participant, Some(*deposit));
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            set_contract_balance(raffle.total_balance());
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.cancel_and_refund(), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.cancel_and_refund(), Ok(()));
+            assert_eq!(raffle.cancelled(), true);
+            assert_eq!(raffle.total_balance(), 0);
+            assert_eq!(raffle.cancel_and_refund(), Err(Error::RaffleCancelled));
+
+            for (participant, deposit) in joined.iter() {
+                let before = ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(*participant)
+                    .expect("Cannot get account balance");
+                do_transfer(*participant, Some(0));
+                assert_eq!(raffle.withdraw(), Ok(()));
+                let after = ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(*participant)
+                    .expect("Cannot get account balance");
+                assert_eq!(after - before, *deposit);
+            }
+
+            do_transfer(accounts.frank, None);
+            assert_eq!(raffle.participate(accounts.frank), Err(Error::RaffleCancelled));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleCancelled));
+
+            // the deployment can run again
+            assert_eq!(raffle.reset(), Ok(()));
+            do_transfer(accounts.frank, None);
+            assert_eq!(raffle.participate(accounts.frank), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_cancel_after_draw() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.cancel_and_refund(), Err(Error::RaffleInProgress));
+        }
+
+        #[ink::test]
+        fn test_weighted_draw() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_weighted(accounts.django, true);
+            let heavy = accounts.frank;
+            let rounds = 40;
+            let mut heavy_wins = 0;
+            for _ in 0..rounds {
+                for participant in [accounts.alice, accounts.bob, accounts.charlie, accounts.eve].iter() {
+                    ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                        .expect("Cannot advance block");
+                    do_transfer(*participant, Some(DEPOSIT_MIN));
+                    assert_eq!(raffle.participate(*participant), Ok(()));
+                }
+                do_transfer(heavy, Some(DEPOSIT_MAX));
+                assert_eq!(raffle.participate(heavy), Ok(()));
+
+                do_transfer(accounts.alice, Some(0));
+                end_countdown(&mut raffle);
+                assert_eq!(raffle.draw_winner(), Ok(()));
+                if raffle.winner_at(0) == Some(heavy) {
+                    heavy_wins += 1;
+                }
+                // the second slot goes to someone else
+                assert_eq!(raffle.draw_winner(), Ok(()));
+                assert_ne!(raffle.winner_at(0), raffle.winner_at(1));
+                assert_eq!(raffle.reset(), Ok(()));
+            }
+            // 10 of 14 parts of the pot, an equal draw would give 1 in 5
+            assert!(heavy_wins > rounds / 2, "heavy depositor won {} of {}", heavy_wins, rounds);
+        }
+
+        #[ink::test]
+        fn test_weighted_index() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_weighted(accounts.django, true);
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.charlie, Some(DEPOSIT_MAX));
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+
+            assert_eq!(raffle.weighted_index(0), 0);
+            assert_eq!(raffle.weighted_index(DEPOSIT_MIN - 1), 0);
+            assert_eq!(raffle.weighted_index(DEPOSIT_MIN), 1);
+            assert_eq!(raffle.weighted_index(DEPOSIT_MIN + DEPOSIT_MAX), 0);
+
+            // winners drop out of the range
+            raffle.winner_list.push((accounts.bob, 0, false));
+            assert_eq!(raffle.weighted_index(0), 1);
+        }
+
+        #[ink::test]
+        fn test_win_probability_bps() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_entry_limit(accounts.django, 3);
+            for participant in &[accounts.bob, accounts.bob, accounts.bob, accounts.charlie, accounts.eve] {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            // 3 of 5 tickets
+            assert_eq!(raffle.win_probability_bps(accounts.bob), 6_000);
+            assert_eq!(raffle.win_probability_bps(accounts.charlie), 2_000);
+            assert_eq!(raffle.win_probability_bps(accounts.frank), 0);
+
+            // bob's tickets leave the pool after bob won
+            raffle.winner_list.push((accounts.bob, 0, false));
+            assert_eq!(raffle.win_probability_bps(accounts.bob), 0);
+            assert_eq!(raffle.win_probability_bps(accounts.charlie), 5_000);
+        }
+
+        #[ink::test]
+        fn test_win_probability_bps_weighted() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_weighted(accounts.django, true);
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.charlie, Some(DEPOSIT_MIN * 3));
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+
+            assert_eq!(raffle.win_probability_bps(accounts.bob), 2_500);
+            assert_eq!(raffle.win_probability_bps(accounts.charlie), 7_500);
+
+            raffle.winner_list.push((accounts.charlie, 0, false));
+            assert_eq!(raffle.win_probability_bps(accounts.bob), 10_000);
+        }
+
+        #[ink::test]
+        fn test_lifetime_stats() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            let mut paid = 0;
+            for round in 1..=2 {
+                set_all_participants(&mut raffle);
+                end_countdown(&mut raffle);
+                assert_eq!(raffle.draw_winner(), Ok(()));
+                assert_eq!(raffle.rounds_completed(), round - 1);
+                assert_eq!(raffle.draw_winner(), Ok(()));
+                paid += raffle.total_balance();
+                assert_eq!(raffle.rounds_completed(), round);
+                assert_eq!(raffle.lifetime_paid(), paid);
+                assert_eq!(raffle.reset(), Ok(()));
+            }
+            assert_eq!(raffle.lifetime_paid(), DEPOSIT_MIN * RAFFLE_TRIGGER as Balance * 2);
+        }
+
+        #[ink::test]
+        fn test_reset() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            // only the owner, and only once the round is over
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.reset(), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.reset(), Err(Error::RaffleInProgress));
+
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let first_pot = raffle.total_balance();
+            assert_eq!(raffle.reset(), Ok(()));
+            assert_eq!(raffle.finished(), false);
+            assert_eq!(raffle.participants(), 0);
+            assert_eq!(raffle.total_balance(), 0);
+            assert!(raffle.winners_in_draw_order().is_empty());
+            assert_eq!(raffle.remaining_time(), u64::MAX);
+
+            // last round's participants can enter the fresh round
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.participants(), RAFFLE_TRIGGER);
+            assert_eq!(raffle.reset(), Err(Error::RaffleInProgress));
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
+
+            // both pots are owed
+            let owed: Balance = raffle.pending_withdrawals.values().sum();
+            assert_eq!(owed, first_pot + raffle.total_balance());
+        }
+
+        #[ink::test]
+        fn test_force_expire() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let fifteen_minutes = 15 * 60 * 1000;
+            let mut raffle = Raffle::new_with_config(accounts.django, 2, RAFFLE_TRIGGER, fifteen_minutes, 0);
+            raffle.force_expire();
+            assert_eq!(raffle.status(), RaffleStatus::AwaitingParticipants);
+
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+            raffle.force_expire();
+            assert_eq!(raffle.remaining_time(), 0);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            let mut raffle = Raffle::new_with_block_countdown(accounts.django, true, 100);
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+            raffle.force_expire();
+            assert_eq!(raffle.draw_winner(), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_close_entries() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let fifteen_minutes = 15 * 60 * 1000;
+            let mut raffle = Raffle::new_with_config(accounts.django, 2, RAFFLE_TRIGGER, fifteen_minutes, 0);
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.close_entries(), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.close_entries(), Ok(()));
+            assert_eq!(raffle.entries_closed(), true);
+            assert_eq!(raffle.status(), RaffleStatus::ReadyToDraw);
+            assert_eq!(raffle.remaining_time(), 0);
+
+            do_transfer(accounts.django, None);
+            assert_eq!(raffle.participate(accounts.django), Err(Error::EntriesClosed));
+            do_transfer(accounts.alice, Some(0));
+
+            // no waiting for the fifteen minutes
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
+            assert_eq!(raffle.close_entries(), Err(Error::RaffleFinished));
+
+            assert_eq!(raffle.reset(), Ok(()));
+            assert_eq!(raffle.entries_closed(), false);
+        }
+
+        #[ink::test]
+        fn test_close_entries_before_trigger() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            for participant in &[accounts.bob, accounts.charlie, accounts.eve] {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.draw_winner(), Err(Error::TooFewParticpants));
+
+            // the pot is big enough, draw from the three
+            assert_eq!(raffle.close_entries(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
+        }
+
+        #[ink::test]
+        fn test_close_entries_too_few_for_winners() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            set_contract_balance(raffle.total_balance());
+
+            // one participant can't fill two prizes, closing would leave
+            // the raffle stuck after the first draw
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.close_entries(), Err(Error::TooFewParticpants));
+            assert_eq!(raffle.entries_closed(), false);
+            assert_eq!(raffle.draw_winner(), Err(Error::TooFewParticpants));
+
+            // the deposit can still be refunded
+            assert_eq!(raffle.cancel_and_refund(), Ok(()));
+            assert_eq!(raffle.pending_withdrawal(accounts.bob), DEPOSIT_MIN);
+        }
+
+        #[ink::test]
+        fn test_snapshot() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            for participant in &[accounts.bob, accounts.charlie] {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            assert_eq!(raffle.snapshot(), RaffleSnapshot {
+                participant_count: 2,
+                total_balance: DEPOSIT_MIN * 2,
+                winners_drawn: 0,
+                start_time: 0,
+                remaining_time: u64::MAX,
+                status: RaffleStatus::AwaitingParticipants,
+            });
+
+            for participant in &[accounts.eve, accounts.frank, accounts.django] {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            assert_eq!(raffle.snapshot().status, RaffleStatus::CountdownActive);
+            do_transfer(accounts.alice, Some(0));
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let snapshot = raffle.snapshot();
+            assert_eq!(snapshot.participant_count, raffle.participant_list.len());
+            assert_eq!(snapshot.total_balance, raffle.total_balance);
+            assert_eq!(snapshot.winners_drawn, raffle.winners);
+            assert_eq!(snapshot.start_time, raffle.start_time);
+            assert_eq!(snapshot.remaining_time, 0);
+            assert_eq!(snapshot.status, RaffleStatus::ReadyToDraw);
+        }
+
+        #[ink::test]
+        fn test_sweep_unclaimed() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_claim_grace(accounts.django, 20);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.sweep_unclaimed(), Err(Error::RaffleInProgress));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let owed: Balance = raffle.pending_withdrawals.values().sum();
+
+            // first winner claims in time
+            let winners = raffle.winners_in_draw_order();
+            set_contract_balance(owed);
+            do_transfer(winners[0], Some(0));
+            assert_eq!(raffle.withdraw(), Ok(()));
+            let claimed = raffle.winner_prizes()[0].1;
+
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.sweep_unclaimed(), Err(Error::GracePeriodActive));
+            for _ in 0..4 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.sweep_unclaimed(), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.sweep_unclaimed(), Ok(()));
+            assert_eq!(raffle.pending_withdrawal(winners[1]), 0);
+            assert_eq!(raffle.pending_withdrawal(accounts.django), owed - claimed);
+            assert_eq!(raffle.sweep_unclaimed(), Err(Error::NothingToWithdraw));
+        }
+
+        #[ink::test]
+        fn test_sweep_unclaimed_keeps_refunds() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_claim_grace(accounts.django, 0);
+            raffle.refund_overpay = true;
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            // frank overpays for bob, the excess waits for frank
+            do_transfer(accounts.frank, Some(DEPOSIT_MAX + 7));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            for participant in &[accounts.alice, accounts.charlie, accounts.eve, accounts.frank] {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            do_transfer(accounts.alice, Some(0));
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_all(), Ok(()));
+
+            let prizes: Balance = raffle.winner_prizes().iter().map(|(_, prize)| prize).sum();
+            let receiver_before = raffle.pending_withdrawal(accounts.django);
+            assert_eq!(raffle.sweep_unclaimed(), Ok(()));
+            assert_eq!(raffle.pending_withdrawal(accounts.django), receiver_before + prizes);
+            // frank's refund survives whether frank won or not
+            assert_eq!(raffle.pending_withdrawal(accounts.frank), 7);
+            for winner in raffle.winners_in_draw_order() {
+                let left = if winner == accounts.frank { 7 } else { 0 };
+                assert_eq!(raffle.pending_withdrawal(winner), left);
+            }
+            assert_eq!(raffle.sweep_unclaimed(), Err(Error::NothingToWithdraw));
+        }
+
+        #[ink::test]
+        fn test_round_id_in_events() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.reset(), Ok(()));
+            let first_round = ink_env::test::recorded_events().count();
+            set_all_participants(&mut raffle);
+
+            // round of every NewParticipant, None for other events
+            let rounds: Vec<Option<u32>> = decoded_events()
+                .into_iter()
+                .map(|event| match event {
+                    Event::NewParticipant(NewParticipant { round_id, .. }) => Some(round_id),
+                    _ => None,
+                })
+                .collect();
+            let (first, second) = rounds.split_at(first_round);
+            assert!(first.iter().flatten().all(|round| *round == 0));
+            let second: Vec<u32> = second.iter().flatten().copied().collect();
+            assert_eq!(second, vec![1; RAFFLE_TRIGGER as usize]);
+        }
+
+        fn draw_fee_raffle(fee_bps: u16) -> Raffle {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_fee(accounts.django, AccountId::from(HOUSE), fee_bps);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            raffle
+        }
+
+        #[ink::test]
+        fn test_house_fee() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let raffle = draw_fee_raffle(MAX_FEE_BPS);
+            assert_eq!(raffle.fee(), (MAX_FEE_BPS, AccountId::from(HOUSE)));
+            let pot = raffle.total_balance();
+            let fee = pot / 10;
+            let share = (pot - fee) / 2;
+            assert_eq!(raffle.pending_withdrawal(AccountId::from(HOUSE)), fee);
+            for winner in raffle.winners_in_draw_order() {
+                assert_eq!(raffle.pending_withdrawal(winner), share);
+            }
+            assert_eq!(raffle.pending_withdrawal(accounts.django), pot - fee - share * 2);
+
+            set_contract_balance(pot);
+            assert_eq!(raffle.solvency_check(), true);
+        }
+
+        #[ink::test]
+        fn test_house_fee_huge_pot() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_fee(accounts.django, AccountId::from(HOUSE), MAX_FEE_BPS);
+            raffle.total_balance = Balance::MAX;
+            // 10% of u128::MAX, rounded down
+            assert_eq!(raffle.house_fee(), 34_028_236_692_093_846_346_337_460_743_176_821_145);
+
+            raffle.total_balance = 19_999;
+            assert_eq!(raffle.house_fee(), 1_999);
+        }
+
+        #[ink::test]
+        fn test_zero_house_fee() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let raffle = draw_fee_raffle(0);
+            let pot = raffle.total_balance();
+            let share = pot / 2;
+            assert_eq!(raffle.pending_withdrawal(AccountId::from(HOUSE)), 0);
+            for winner in raffle.winners_in_draw_order() {
+                assert_eq!(raffle.pending_withdrawal(winner), share);
+            }
+            assert_eq!(raffle.pending_withdrawal(accounts.django), pot - share * 2);
+        }
+
+        #[ink::test]
+        #[should_panic]
+        fn test_house_fee_above_cap() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            Raffle::new_with_fee(accounts.django, AccountId::from(HOUSE), MAX_FEE_BPS + 1);
+        }
+
+        #[ink::test]
+        fn test_get_winners() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert!(raffle.get_winners().is_empty());
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            let winners = raffle.get_winners();
+            assert_eq!(winners.len(), 1);
+            assert_eq!(winners.len() as u32, raffle.winners);
+            assert_eq!(Some(winners[0]), raffle.winner_address()[0]);
+        }
+
+        #[ink::test]
+        fn test_winner_at() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            assert_eq!(raffle.winner_at(0), raffle.winner_address()[0]);
+            assert!(raffle.winner_at(0).is_some());
+            assert_eq!(raffle.winner_at(1), None);
+            assert_eq!(raffle.winner_at(RAFFLE_WINNERS), None);
+        }
+
+        #[ink::test]
+        fn test_withdrawals_paused() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            // alice deploys and owns the raffle
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            set_contract_balance(raffle.total_balance());
+            let winner = raffle.winners_in_draw_order()[0];
+            let share = raffle.pending_withdrawal(winner);
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.pause_withdrawals(), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.pause_withdrawals(), Ok(()));
+            assert_eq!(raffle.withdrawals_paused(), true);
+
+            do_transfer(winner, Some(0));
+            assert_eq!(raffle.withdraw(), Err(Error::WithdrawalsPaused));
+            assert_eq!(raffle.pending_withdrawal(winner), share);
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.unpause_withdrawals(), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.unpause_withdrawals(), Ok(()));
+            assert_eq!(raffle.withdrawals_paused(), false);
+
+            do_transfer(winner, Some(0));
+            assert_eq!(raffle.withdraw(), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_entries_to_reach() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.entries_to_reach(DEPOSIT_MIN * 3), 3);
+
+            // average deposit is 2 x DEPOSIT_MIN, pot is 6 x DEPOSIT_MIN
+            do_transfer(accounts.alice, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN * 2));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.charlie, Some(DEPOSIT_MIN * 3));
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+
+            assert_eq!(raffle.entries_to_reach(DEPOSIT_MIN * 10), 2);
+            assert_eq!(raffle.entries_to_reach(DEPOSIT_MIN * 11), 3);
+            assert_eq!(raffle.entries_to_reach(DEPOSIT_MIN * 5), 0);
+        }
+
+        #[ink::test]
+        fn test_draw_nonce_reseeds() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+
+            // both draws happen in the same block
+            let first_seed = raffle.draw_seed();
+            let first_random = raffle.get_random_number();
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_nonce, 1);
+            assert_ne!(raffle.draw_seed(), first_seed);
+            assert_ne!(raffle.get_random_number(), first_random);
+        }
+
+        #[ink::test]
+        fn test_draw_seed_mixes_chain_state() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+
+            let first_seed = raffle.draw_seed();
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            let second_seed = raffle.draw_seed();
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_ne!(first_seed, second_seed);
+
+            // a new block alone changes the seed
+            let seed = raffle.draw_seed();
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_ne!(raffle.draw_seed(), seed);
+        }
+
+        #[ink::test]
+        fn test_draw_reentrancy() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_bonus(accounts.django, AccountId::from([0x10; 32]), BONUS);
+            set_all_participants(&mut raffle);
+            // a failed draw releases the lock too
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+            assert_eq!(raffle.locked, false);
+            end_countdown(&mut raffle);
+            set_contract_balance(raffle.total_balance());
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            // the bonus token calls back in while the last draw pays it
+            TOKEN_REENTER.with(|reenter| reenter.set(Some(&mut raffle as *mut Raffle)));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            REENTRY_RESULTS.with(|results| {
+                assert_eq!(*results.borrow(), vec![Err(Error::Reentrancy)])
+            });
+            assert_eq!(raffle.locked, false);
+            assert_eq!(raffle.winners, RAFFLE_WINNERS);
+            // every winner still got the bonus
+            TOKEN_TRANSFERS.with(|transfers| {
+                assert_eq!(transfers.borrow().len(), RAFFLE_WINNERS as usize)
+            });
+        }
+
+        #[ink::test]
+        fn test_is_winner() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.is_winner(accounts.bob), false);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            let winner = raffle.winner_at(0).expect("slot 0 drawn");
+            assert_eq!(raffle.is_winner(winner), true);
+            let others = raffle.get_participants().into_iter().filter(|a| *a != winner);
+            for participant in others {
+                assert_eq!(raffle.is_winner(participant), false);
+            }
+            assert_eq!(raffle.is_winner(accounts.django), false);
+        }
+
+        #[ink::test]
+        fn test_winner_event_slot() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            let slots: Vec<(u32, Option<AccountId>)> = winner_events()
+                .into_iter()
+                .map(|RaffleWinner { slot, winner, .. }| (slot, winner))
+                .collect();
+            let winners = raffle.winners_in_draw_order();
+            assert_eq!(slots, vec![(0, Some(winners[0])), (1, Some(winners[1]))]);
+        }
+
+        #[ink::test]
+        fn test_raffle_finished_event() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleFinished));
+
+            let finished: Vec<(Balance, u32)> = decoded_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::RaffleFinished(RaffleFinished { total_paid, winner_count, .. }) => {
+                        Some((total_paid, winner_count))
+                    }
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(finished, vec![(DEPOSIT_MIN * RAFFLE_TRIGGER as Balance, RAFFLE_WINNERS)]);
+        }
+
+        #[ink::test]
+        fn test_injected_random() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+
+            // tickets are alice, bob, charlie, eve, frank: 7 % 5 picks charlie
+            set_random(&[7, 2]);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let (winner, index, random, _, _) = raffle.winner_audit(0).expect("winner drawn");
+            assert_eq!((winner, index, random), (accounts.charlie, 2, 7));
+
+            // charlie's ticket is taken, the draw moves on to eve
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let (winner, index, random, _, _) = raffle.winner_audit(1).expect("winner drawn");
+            assert_eq!((winner, index, random), (accounts.eve, 3, 2));
+        }
+
+        #[ink::test]
+        fn test_injected_random_weighted() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_weighted(accounts.django, true);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+
+            // 0 lands on the first deposit, alice's
+            set_random(&[0, 5]);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let (winner, index, random, _, _) = raffle.winner_audit(0).expect("winner drawn");
+            assert_eq!((winner, index, random), (accounts.alice, 0, 0));
+            // the weighted draw took a single value
+            assert_eq!(RANDOM_VALUES.with(|values| values.borrow().clone()), vec![5]);
+
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.winner_audit(1).expect("winner drawn").2, 5);
+            assert_eq!(winner_events()[1].random, 5);
+            assert!(RANDOM_VALUES.with(|values| values.borrow().is_empty()));
+        }
+
+        #[ink::test]
+        fn test_audit_records_redrawn_random() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+
+            // u32::MAX is in the biased range for 5 tickets and gets redrawn
+            set_random(&[u32::MAX, 7]);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            let (winner, index, random, pool_size, _) = raffle.winner_audit(0).expect("winner drawn");
+            assert_eq!((winner, index, random), (accounts.charlie, 2, 7));
+            assert_eq!(raffle.draw_candidates()[(random % pool_size) as usize], winner);
+            assert_eq!(winner_events()[0].random, 7);
+        }
+
+        #[ink::test]
+        fn test_raffle_open_time_remaining() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+
+            let remaining: Vec<u64> = decoded_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::RaffleOpen(RaffleOpen { time_remaining, .. }) => Some(time_remaining),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(remaining, vec![DURATION_IN_MS]);
+            assert_eq!(raffle.remaining_time(), DURATION_IN_MS);
+        }
+
+        #[ink::test]
+        fn test_draw_all() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.draw_all(), Err(Error::RaffleStillOpen));
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_all(), Ok(()));
+            assert!(raffle.finished());
+            assert_eq!(raffle.winners, RAFFLE_WINNERS);
+            let winners = raffle.winners_in_draw_order();
+            assert_eq!(winners.len() as u32, RAFFLE_WINNERS);
+            assert_ne!(winners[0], winners[1]);
+            assert_eq!(raffle.draw_all(), Err(Error::RaffleFinished));
+
+            let (drawn, finished) = decoded_events().iter().fold((0, 0), |(w, f), event| {
+                match event {
+                    Event::RaffleWinner(_) => (w + 1, f),
+                    Event::RaffleFinished(_) => (w, f + 1),
+                    _ => (w, f),
+                }
+            });
+            assert_eq!((drawn, finished), (RAFFLE_WINNERS, 1));
+        }
+
+        #[ink::test]
+        fn test_winner_event_random() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            let events = winner_events();
+            assert_eq!(events.len(), 1);
+            let RaffleWinner { winner, index, random, block, slot, .. } = events[0];
+            let (audit_winner, audit_index, audit_random, _, audit_block) =
+                raffle.winner_audit(0).expect("winner drawn");
+            assert_eq!(winner, Some(audit_winner));
+            assert_eq!(index, audit_index);
+            assert_ne!(random, 0);
+            assert_eq!(random, audit_random);
+            assert_eq!(block as u64, audit_block);
+            assert_eq!(slot, 0);
+        }
+
+        #[ink::test]
+        fn test_unbiased_index() {
+            // 7 doesn't divide 2^32, the top 2^32 % 7 = 4 values are biased
+            let len = 7;
+            let limit = u32::MAX - 4;
+
+            let batch = 7 * 1_000;
+            let mut counts = [0u32; 7];
+            for random in (limit - batch + 1)..=limit {
+                let (index, kept) = Raffle::unbiased_index(random, len, || panic!("no redraw expected"));
+                assert_eq!(kept, random);
+                counts[index as usize] += 1;
+            }
+            assert_eq!(counts, [1_000; 7]);
+
+            // anything above limit is redrawn
+            for random in (limit + 1)..=u32::MAX {
+                let mut redraws = vec![u32::MAX, 12];
+                let picked = Raffle::unbiased_index(random, len, || redraws.remove(0));
+                assert_eq!(picked, (12 % 7, 12));
+                assert!(redraws.is_empty());
+            }
+
+            // a divisor of 2^32 never redraws
+            assert_eq!(Raffle::unbiased_index(u32::MAX, 4, || panic!("no redraw expected")), (3, u32::MAX));
+        }
+
+        #[ink::test]
+        fn test_is_participating_many() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            for n in 0x20..0x40u8 {
+                let participant = AccountId::from([n; 32]);
+                do_transfer(participant, None);
+                assert_eq!(raffle.participate(participant), Ok(()));
+            }
+            assert_eq!(raffle.participants(), 0x20);
+            for n in 0x20..0x40u8 {
+                assert_eq!(raffle.is_participating(AccountId::from([n; 32])), true);
+            }
+            assert_eq!(raffle.is_participating(AccountId::from([0x40; 32])), false);
+            assert_eq!(raffle.is_participating(accounts.alice), false);
+
+            let participant = AccountId::from([0x30; 32]);
+            do_transfer(participant, None);
+            assert_eq!(raffle.participate(participant), Err(Error::AlreadyParticipating));
+        }
+
+        #[ink::test]
+        fn test_many_winners() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            // more winners than a u8 could count
+            let winners = 260;
+            let mut raffle = Raffle::new_with_config(accounts.django, winners, winners + 1, DURATION_IN_MS, 0);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            for n in 0..=winners {
+                let mut id = [0x40; 32];
+                id[..4].copy_from_slice(&n.to_be_bytes());
+                let participant = AccountId::from(id);
+                do_transfer(participant, None);
+                assert_eq!(raffle.participate(participant), Ok(()));
+            }
+            do_transfer(accounts.alice, Some(0));
+            end_countdown(&mut raffle);
+            for _ in 0..winners {
+                assert_eq!(raffle.draw_winner(), Ok(()));
+            }
+            assert_eq!(raffle.finished(), true);
+            assert_eq!(raffle.winners, winners);
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleFinished));
+            assert_eq!(raffle.winner_at(winners - 1).is_some(), true);
+        }
+
+        #[ink::test]
+        fn test_new_participant_count() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.charlie, Some(DEPOSIT_MAX));
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+
+            let emitted_events = decoded_events();
+            assert_eq!(emitted_events.len(), 2);
+            let joined: Vec<(Option<AccountId>, Balance, u32)> = emitted_events
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::NewParticipant(NewParticipant { participant, value, count, .. }) => {
+                        Some((participant, value, count))
+                    }
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(joined, vec![
+                (Some(accounts.bob), DEPOSIT_MIN, 1),
+                (Some(accounts.charlie), DEPOSIT_MAX, 2),
+            ]);
+        }
+
+        #[ink::test]
+        fn test_deposit_of() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.charlie, Some(DEPOSIT_MAX));
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+
+            assert_eq!(raffle.deposit_of(accounts.bob), DEPOSIT_MIN);
+            assert_eq!(raffle.deposit_of(accounts.charlie), DEPOSIT_MAX);
+            assert_eq!(raffle.deposit_of(accounts.eve), 0);
+        }
+
+        #[ink::test]
+        fn test_my_receipt() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.alice, None);
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            let joined_at = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block timestamp");
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN * 2));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.my_receipt(), Some((1, DEPOSIT_MIN * 2, joined_at)));
+
+            do_transfer(accounts.django, Some(0));
+            assert_eq!(raffle.my_receipt(), None);
+        }
+
+        #[ink::test]
+        fn test_scheduled_open() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let now = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block timestamp");
+            let mut raffle = Raffle::new_scheduled(accounts.django, now + 1);
+            assert_eq!(raffle.scheduled_open(), now + 1);
+            assert_eq!(raffle.is_open(), false);
+
+            do_transfer(accounts.alice, None);
+            assert_eq!(raffle.participate(accounts.alice), Err(Error::NotYetOpen));
+
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(raffle.is_open(), true);
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_rate_limited() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_rate_limited(accounts.django, 2);
+            do_transfer(accounts.alice, None);
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.charlie, None);
+            assert_eq!(raffle.participate(accounts.charlie), Err(Error::RateLimited));
+
+            // counter resets in the next block
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+        }
+
+        #[ink::test]
+        fn test_target_block() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.set_target_block(1_000), Err(Error::TooFewParticpants));
+
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            let now = ink_env::block_number::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block number");
+
+            do_transfer(accounts.bob, Some(0));
+            assert_eq!(raffle.set_target_block(now + 1), Err(Error::NotOwner));
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.set_target_block(now), Err(Error::InvalidTargetBlock));
+            assert_eq!(raffle.set_target_block(now + 1), Ok(()));
+            assert_eq!(raffle.target_block(), now + 1);
+            assert_eq!(raffle.draw_seed()[36..], (now + 1).to_be_bytes());
+
+            assert_eq!(raffle.draw_winner(), Err(Error::TargetBlockNotReached));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(raffle.draw_winner(), Err(Error::TargetBlockNotReached));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            // can't be moved once drawing started
+            assert_eq!(raffle.set_target_block(now + 10), Err(Error::InvalidTargetBlock));
+        }
+
+        #[ink::test]
+        fn test_winner_audit() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.winner_audit(0), None);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+
+            let (winner, index, random, pool_size, block) =
+                raffle.winner_audit(0).expect("slot 0 drawn");
+            let now = ink_env::block_number::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block number");
+            assert_eq!(block, now as u64);
+            assert_eq!(pool_size, raffle.participants());
+            assert_eq!(index, random % pool_size);
+            assert_eq!(raffle.draw_candidates()[index as usize], winner);
+            assert_eq!(raffle.winner_audit(1), None);
+        }
+
+        #[ink::test]
+        fn test_no_repeat_winner() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+
+            // RNG lands on charlie (index 2) who already won
+            raffle.winner_list.push((accounts.charlie, 0, false));
+            assert_eq!(raffle.next_non_winner(2), 3);
+            // and wraps around past the end of the list
+            raffle.winner_list.pop();
+            raffle.winner_list.push((accounts.frank, 0, false));
+            assert_eq!(raffle.next_non_winner(4), 0);
+            raffle.winner_list.pop();
+
+            set_contract_balance(raffle.total_balance());
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_ne!(raffle.winner_at(0), raffle.winner_at(1));
+        }
+
+        #[ink::test]
+        fn test_no_repeat_winner_pool_equals_winners() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            do_transfer(accounts.alice, None);
+            assert_eq!(raffle.participate(accounts.alice), Ok(()));
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.participants(), RAFFLE_WINNERS);
+
+            raffle.winner_list.push((accounts.alice, 0, false));
+            assert_eq!(raffle.next_non_winner(0), 1);
+            assert_eq!(raffle.next_non_winner(1), 1);
+        }
+
+        #[ink::test]
+        fn test_next_winner_preview() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.next_winner_preview(), None);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+
+            let events = ink_env::test::recorded_events().count();
+            let preview = raffle.next_winner_preview().expect("draw possible");
+            assert!(raffle.get_participants().contains(&preview));
+            assert_eq!(raffle.winners, 0);
+            assert_eq!(raffle.draw_nonce, 0);
+            assert_eq!(ink_env::test::recorded_events().count(), events);
+
+            // same block, same state: the draw picks the previewed account
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.winner_at(0), Some(preview));
+
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.next_winner_preview(), None);
+        }
+
+        #[ink::test]
+        fn test_pool_below_winner_count() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_config(accounts.django, 3, RAFFLE_TRIGGER, DURATION_IN_MS, 0);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            for participant in &[accounts.bob, accounts.charlie, accounts.eve, accounts.frank] {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            set_contract_balance(raffle.total_balance());
+
+            // two leave, two accounts are left for three prizes
+            for participant in &[accounts.charlie, accounts.frank] {
+                do_transfer(*participant, Some(0));
+                assert_eq!(raffle.leave(), Ok(()));
+            }
+            assert_eq!(raffle.participants(), 2);
+
+            do_transfer(accounts.alice, Some(0));
+            assert_eq!(raffle.close_entries(), Err(Error::TooFewParticpants));
+            assert_eq!(raffle.draw_winner(), Err(Error::TooFewParticpants));
+            assert_eq!(raffle.draw_all(), Err(Error::TooFewParticpants));
+            assert_eq!(raffle.winners, 0);
+        }
+
+        #[ink::test]
+        fn test_balance_overflow() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            raffle.total_balance = Balance::MAX - DEPOSIT_MIN + 1;
+            do_transfer(accounts.alice, None);
+            assert_eq!(raffle.participate(accounts.alice), Err(Error::BalanceOverflow));
+            assert_eq!(raffle.is_participating(accounts.alice), false);
+            assert_eq!(raffle.participants(), 0);
+            assert_eq!(raffle.total_balance(), Balance::MAX - DEPOSIT_MIN + 1);
+            assert_eq!(ink_env::test::recorded_events().count(), 0);
         }
-        fn as_u32_be(arr: &[u8]) -> u32 {
-            ((arr[0] as u32) << 24)
-                + ((arr[1] as u32) << 16)
-                + ((arr[2] as u32) << 8)
-                + ((arr[3] as u32) << 0)
+
+        #[ink::test]
+        fn test_draw_start_time_in_future() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            set_all_participants(&mut raffle);
+
+            let now = ink_env::block_timestamp::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block timestamp");
+            raffle.start_time = now + raffle.duration_ms * 10;
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+
+            raffle.start_time = 0;
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+            assert_eq!(raffle.winners, 0);
         }
-    }
 
+        #[ink::test]
+        fn test_single_winner_config() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
 
-    #[cfg(test)]
-    mod tests {
-        use ink_lang as ink;
-        use super::*;
+            let mut raffle = Raffle::new_with_config(accounts.django, 1, RAFFLE_TRIGGER, DURATION_IN_MS, 0);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            set_contract_balance(raffle.total_balance());
 
-        /// We test if the default constructor does its job.
-        #[test]
-        fn default_works() {
+            assert_eq!(raffle.winner_address(), [None]);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
+            assert_eq!(raffle.winner_address().len(), 1);
+            assert!(raffle.winner_address()[0].is_some());
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleFinished));
+        }
+
+        #[ink::test]
+        fn test_five_winner_config() {
             let accounts =
                 ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
                     .expect("Cannot get accounts");
-            let raffle = Raffle::new(accounts.alice);
-            assert_eq!(raffle.participants(), 0);
-            assert_eq!(raffle.pot_receiver, accounts.alice);
+
+            let mut raffle = Raffle::new_with_config(accounts.django, 5, RAFFLE_TRIGGER, DURATION_IN_MS, 0);
+            set_all_participants(&mut raffle);
+            end_countdown(&mut raffle);
+            set_contract_balance(raffle.total_balance());
+
+            for _ in 0..4 {
+                assert_eq!(raffle.draw_winner(), Ok(()));
+                assert_eq!(raffle.finished(), false);
+            }
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
+
+            // every participant won exactly once
+            let mut winners = raffle.winners_in_draw_order();
+            winners.sort();
+            let mut participants = raffle.draw_candidates();
+            participants.sort();
+            assert_eq!(winners, participants);
         }
 
-        /// We test a simple use case of our contract.
-        #[test]
-        fn test_participate() {
+        #[ink::test]
+        #[should_panic]
+        fn test_zero_winner_config() {
             let accounts =
                 ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
                     .expect("Cannot get accounts");
-            
-            let mut raffle = Raffle::new(accounts.alice);
-            do_transfer(accounts.bob, None);
-            assert_eq!(raffle.participate(accounts.bob), Ok(()));
-            assert_eq!(raffle.is_participating(accounts.bob), true);
-            
-            // Expect one emitted event:
-            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 1);
+            Raffle::new_with_config(accounts.django, 0, RAFFLE_TRIGGER, DURATION_IN_MS, 0);
         }
-        /// A user can send in anywhere between 0.01 and 0.1 tokens.
-        #[test]
-        fn test_deposit_limits() {
+
+        #[ink::test]
+        fn test_trigger_config() {
             let accounts =
-              ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
-              .expect("Cannot get accounts");
-            
-            let mut raffle = Raffle::new(accounts.alice);
-            
-            do_transfer(accounts.bob, Some(DEPOSIT_MIN- 1));
-            assert_eq!(raffle.participate(accounts.charlie), Err(Error::EndowmentOutOfLimits));
-            assert_eq!(raffle.is_participating(accounts.charlie), false);
-            
-            do_transfer(accounts.bob, Some(DEPOSIT_MAX+ 1));
-            assert_eq!(raffle.participate(accounts.charlie), Err(Error::EndowmentOutOfLimits));
-            assert_eq!(raffle.is_participating(accounts.charlie), false);
-            
-            do_transfer(accounts.bob, None);
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_config(accounts.django, 2, 3, DURATION_IN_MS, 0);
+            for participant in [accounts.alice, accounts.bob].iter() {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            assert_eq!(raffle.enough_participants, false);
+            assert_eq!(raffle.start_time, 0);
+
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            do_transfer(accounts.charlie, None);
             assert_eq!(raffle.participate(accounts.charlie), Ok(()));
-            assert_eq!(raffle.is_participating(accounts.charlie), true);
+            assert_eq!(raffle.enough_participants, true);
+            assert_ne!(raffle.start_time, 0);
+
+            do_transfer(accounts.alice, Some(0));
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
         }
 
-        /// 15 minute countdown only starts once there are at least 5 players in the pool.
         #[ink::test]
-        fn test_draw() {
+        fn test_block_countdown() {
             let accounts =
                 ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
                     .expect("Cannot get accounts");
-            
-            let mut raffle = Raffle::new(accounts.charlie);
+
+            let mut raffle = Raffle::new_with_block_countdown(accounts.django, true, 3);
             set_all_participants(&mut raffle);
+            let start_block = ink_env::block_number::<ink_env::DefaultEnvironment>()
+                .expect("Cannot get block number");
+            assert_eq!(raffle.start_block, start_block);
 
-            // Draw fails since countdown just started
+            // there is no millisecond deadline to announce
+            let deadlines: Vec<Option<u64>> = decoded_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::RaffleTriggered(RaffleTriggered { deadline, .. }) => Some(deadline),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(deadlines, vec![None]);
+            assert_eq!(raffle.deadline(), None);
+
+            // timestamps are ignored, only block height counts
+            raffle.start_time = 1;
+            for _ in 0..2 {
+                assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+                assert_eq!(raffle.status(), RaffleStatus::CountdownActive);
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
             assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+            assert_eq!(raffle.remaining_time(), u64::MAX);
+
             ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
                 .expect("Cannot advance block");
-            let dbg_msg = format!( "start_time {:#?}", raffle.start_time );
-            ink_env::debug_println( &dbg_msg );
+            assert_eq!(raffle.status(), RaffleStatus::ReadyToDraw);
+            assert_eq!(raffle.remaining_time(), 0);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+        }
 
-            assert_ne!(raffle.start_time, 0);
+        #[ink::test]
+        fn test_participants_until_trigger() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.participants_until_trigger(), RAFFLE_TRIGGER);
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            assert_eq!(raffle.participants_until_trigger(), RAFFLE_TRIGGER - 1);
+            do_transfer(accounts.charlie, None);
+            assert_eq!(raffle.participate(accounts.charlie), Ok(()));
+            assert_eq!(raffle.participants_until_trigger(), RAFFLE_TRIGGER - 2);
+            for participant in [accounts.eve, accounts.frank, accounts.alice].iter() {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            assert_eq!(raffle.participants_until_trigger(), 0);
+        }
 
-            // fake the time pass. Move it in time backwards
-            raffle.start_time -= DURATION_IN_MS * 2; // for test to pass set DURATION_IN_MS=5
+        #[ink::test]
+        fn test_blocks_until_draw() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
 
-            // draw 2 winners
+            let mut raffle = Raffle::new_with_block_countdown(accounts.django, true, 3);
+            assert_eq!(raffle.blocks_until_draw(), u32::MAX);
+            set_all_participants(&mut raffle);
+            for remaining in (1..=3).rev() {
+                assert_eq!(raffle.blocks_until_draw(), remaining);
+                assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+            assert_eq!(raffle.blocks_until_draw(), 0);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            assert_eq!(raffle.blocks_until_draw(), 0);
             assert_eq!(raffle.draw_winner(), Ok(()));
-            assert_eq!(raffle.winners, 1);
+        }
+
+        #[ink::test]
+        fn test_deadline() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.deadline(), None);
+            set_all_participants(&mut raffle);
+            let start_time = raffle.start_time;
+            assert_eq!(raffle.deadline(), Some(start_time + DURATION_IN_MS));
+            // absolute, doesn't move as time passes
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.deadline(), Some(start_time + DURATION_IN_MS));
+
+            raffle.start_time = u64::MAX - 1;
+            assert_eq!(raffle.deadline(), Some(u64::MAX));
+
+            let mut raffle = Raffle::new_with_block_countdown(accounts.django, true, 3);
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.deadline(), None);
+        }
+
+        #[ink::test]
+        fn test_blocks_until_draw_time_countdown() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.blocks_until_draw(), u32::MAX);
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.blocks_until_draw(), u32::MAX);
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.blocks_until_draw(), 0);
+        }
+
+        #[ink::test]
+        #[should_panic]
+        fn test_block_countdown_zero_blocks() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            Raffle::new_with_block_countdown(accounts.django, true, 0);
+        }
+
+        #[ink::test]
+        fn test_duration_config() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let fifteen_minutes = 15 * 60 * 1000;
+            let mut raffle = Raffle::new_with_config(accounts.django, 2, RAFFLE_TRIGGER, fifteen_minutes, 0);
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.remaining_time(), fifteen_minutes);
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
+
+            // each block moves the clock by 5ms
+            while raffle.remaining_time() > 5 {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+            }
+            assert_eq!(raffle.draw_winner(), Err(Error::RaffleStillOpen));
             ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
                 .expect("Cannot advance block");
-            // assert_eq!(raffle.draw_winner(), Ok(())); //this fails with Err(TransferError)
-            // assert_eq!(raffle.winners, 2);
+            assert_eq!(raffle.remaining_time(), 0);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+        }
 
-            // Expect events: 5 NewParticipant events, 1 RaffleOpen, 1 RaffleWinner
-            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 7);
+        #[ink::test]
+        #[should_panic]
+        fn test_zero_duration_config() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            Raffle::new_with_config(accounts.django, 2, RAFFLE_TRIGGER, 0, 0);
         }
 
-        /// There are at least 5 players in the pool.
         #[ink::test]
-        fn test_draw_not_enough_participants() {
+        fn test_max_participants_config() {
             let accounts =
                 ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
                     .expect("Cannot get accounts");
-            
-            let mut raffle = Raffle::new(accounts.charlie);
-            do_transfer(accounts.alice, None);
+
+            let mut raffle = Raffle::new_with_config(accounts.django, 2, 3, DURATION_IN_MS, 4);
+            for participant in [accounts.alice, accounts.bob, accounts.charlie, accounts.eve].iter() {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            do_transfer(accounts.frank, None);
+            assert_eq!(raffle.participate(accounts.frank), Err(Error::PoolFull));
+            assert_eq!(raffle.participants(), 4);
+
+            // uncapped
+            let mut raffle = Raffle::new_with_config(accounts.django, 2, RAFFLE_TRIGGER, DURATION_IN_MS, 0);
+            set_all_participants(&mut raffle);
+            do_transfer(accounts.django, None);
+            assert_eq!(raffle.participate(accounts.django), Ok(()));
+            assert_eq!(raffle.participants(), RAFFLE_TRIGGER + 1);
+        }
+
+        #[ink::test]
+        #[should_panic]
+        fn test_max_participants_below_trigger_config() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            Raffle::new_with_config(accounts.django, 2, 3, DURATION_IN_MS, 2);
+        }
+
+        #[ink::test]
+        #[should_panic]
+        fn test_trigger_below_winners_config() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+            Raffle::new_with_config(accounts.django, 3, 2, DURATION_IN_MS, 0);
+        }
+
+        #[ink::test]
+        fn test_trigger_equals_winners_config() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            // every participant wins, each draw still has a non-winner left
+            let mut raffle = Raffle::new_with_config(accounts.django, 2, 2, DURATION_IN_MS, 0);
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                .expect("Cannot advance block");
+            for participant in &[accounts.bob, accounts.charlie] {
+                do_transfer(*participant, None);
+                assert_eq!(raffle.participate(*participant), Ok(()));
+            }
+            do_transfer(accounts.alice, Some(0));
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.draw_winner(), Ok(()));
+            assert_eq!(raffle.finished(), true);
+        }
+
+        #[ink::test]
+        fn test_deposit_limits_config() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new_with_limits(accounts.django, DEPOSIT_MIN / 10, DEPOSIT_MAX * 10);
+            assert_eq!(raffle.deposit_limits(), (DEPOSIT_MIN / 10, DEPOSIT_MAX * 10));
+
+            // rejected by the default window
+            do_transfer(accounts.alice, Some(DEPOSIT_MAX + 1));
             assert_eq!(raffle.participate(accounts.alice), Ok(()));
-            assert_eq!(raffle.winners, 0);
+            do_transfer(accounts.bob, Some(DEPOSIT_MIN / 10));
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
+            do_transfer(accounts.charlie, Some(DEPOSIT_MAX * 10 + 1));
+            assert_eq!(raffle.participate(accounts.charlie), Err(Error::EndowmentOutOfLimits));
 
-            // Expect events: 1 NewParticipant event
-            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
-            assert_eq!(emitted_events.len(), 1);
+            raffle.deposit_min = DEPOSIT_MAX;
+            raffle.deposit_max = DEPOSIT_MIN;
+            assert_eq!(raffle.validate_config(), Err(Error::InvalidConfig));
         }
-        
 
-        /// A user can only play once.
-        #[test]
-        fn test_play_once() {
+        #[ink::test]
+        #[should_panic]
+        fn test_deposit_limits_config_min_above_max() {
             let accounts =
                 ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
                     .expect("Cannot get accounts");
-            
-            let mut raffle = Raffle::new(accounts.charlie);
+            Raffle::new_with_limits(accounts.django, DEPOSIT_MAX, DEPOSIT_MIN);
+        }
+
+        #[ink::test]
+        fn test_get_participants() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
+
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.get_participants(), Vec::<AccountId>::new());
+
+            do_transfer(accounts.bob, None);
+            assert_eq!(raffle.participate(accounts.bob), Ok(()));
             do_transfer(accounts.alice, None);
             assert_eq!(raffle.participate(accounts.alice), Ok(()));
-            do_transfer(accounts.alice, None);
-            assert_eq!(raffle.participate(accounts.alice), Err(Error::AlreadyParticipating));
+            assert_eq!(raffle.get_participants(), [accounts.bob, accounts.alice]);
+        }
+
+        #[ink::test]
+        fn test_remaining_time() {
+            let accounts =
+                ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot get accounts");
 
+            let mut raffle = Raffle::new(accounts.django);
+            assert_eq!(raffle.remaining_time(), u64::MAX);
+
+            // countdown starts with the fifth participant
+            set_all_participants(&mut raffle);
+            assert_eq!(raffle.remaining_time(), raffle.duration_ms);
+
+            end_countdown(&mut raffle);
+            assert_eq!(raffle.remaining_time(), 0);
+
+            // queries don't emit events
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 6);
+        }
+
+        /// Every event recorded so far, decoded into the contract's Event
+        fn decoded_events() -> Vec<Event> {
+            ink_env::test::recorded_events()
+                .map(|event| {
+                    <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer")
+                })
+                .collect()
+        }
+
+        /// Every RaffleWinner event recorded so far, in draw order
+        fn winner_events() -> Vec<RaffleWinner> {
+            decoded_events()
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::RaffleWinner(winner) => Some(winner),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        /// Let the countdown elapse so the next draw is allowed
+        fn end_countdown(raffle: &mut Raffle) {
+            loop {
+                ink_env::test::advance_block::<ink_env::DefaultEnvironment>()
+                    .expect("Cannot advance block");
+                if raffle.remaining_time() == 0 {
+                    break
+                }
+            }
         }
 
         fn set_all_participants(raffle: &mut Raffle) {
@@ -397,6 +5575,17 @@ mod raffle {
             assert_eq!(raffle.participate(accounts.frank), Ok(()));
 
             assert_eq!(raffle.enough_participants, true);
+
+            // back to alice, the owner
+            do_transfer(accounts.alice, Some(0));
+        }
+
+        fn set_contract_balance(balance: Balance) {
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(
+                AccountId::from([0x07; 32]),
+                balance,
+            )
+            .expect("Cannot set contract balance");
         }
 
         fn do_transfer(caller: AccountId, amount: Option<Balance>){